@@ -75,6 +75,21 @@ Supported devices
 
 [^1]: Tested on real hardware.
 
+The following devices are out of scope and not supported. Support for each
+device in ncc is checked against SysEx produced by Novation Components, which
+hasn’t been captured for these devices:
+
+* Launchpad Pro \[MK3]: not supported. Its 8×8 grid is likely similar to the
+  Launchpad X, but the surround buttons, per-pad aftertouch, and the device’s
+  custom mode message have not been examined.
+* Launchkey \[MK4] and Launchkey Mini \[MK4]: not supported. Custom modes on
+  these devices use encoders rather than pots and have a different set of
+  controls, so they can’t reuse the \[MK3] formats, and their messages have
+  not been examined.
+* Launch Control XL \[MK3]: not supported. Its knobs, faders, and buttons
+  would need new kinds of controls, and its custom mode message has not been
+  examined.
+
 ifdef({RUST},, {dnl
 Installation
 ------------
@@ -199,6 +214,14 @@ for ncc.
 
 [examples]: ifdef({RUST}, {REPO/tree/COMMIT/})examples/
 
+A `.syx` file previously compiled by ncc can be turned back into an equivalent
+TOML file with `ncc decompile <file>`. The output is guaranteed to compile to
+exactly the same SysEx as the input:
+
+```console
+$ ncc decompile example-pads.syx -o recovered.toml
+```
+
 Installing custom modes
 -----------------------
 
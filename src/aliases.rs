@@ -0,0 +1,51 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! User-defined names for values, from the top-level `[aliases]` table.
+//!
+//! Aliases are loaded before the rest of the custom mode is deserialized,
+//! and are passed to the types that accept them (like [`MidiNote`]) in their
+//! deserialization config.
+
+use super::common::{MidiNote, MidiValue};
+use std::collections::BTreeMap;
+
+/// Aliases for each kind of value.
+#[derive(Clone, Debug, Default)]
+pub struct Aliases {
+    /// From `[aliases.notes]`.
+    pub notes: BTreeMap<String, MidiNote>,
+    /// CC numbers, from `[aliases.cc]`.
+    pub cc: BTreeMap<String, MidiValue>,
+    /// Program numbers, from `[aliases.programs]`.
+    pub programs: BTreeMap<String, MidiValue>,
+}
+
+impl Aliases {
+    /// The keys of the `[aliases]` table.
+    pub const KINDS: &'static [&'static str] = &["notes", "cc", "programs"];
+
+    pub const fn new() -> Self {
+        Self {
+            notes: BTreeMap::new(),
+            cc: BTreeMap::new(),
+            programs: BTreeMap::new(),
+        }
+    }
+}
@@ -24,6 +24,7 @@ use std::path::PathBuf;
 
 const USAGE: &str = "\
 [options] <input>
+       {bin} decompile [options] <input>
 
 Compiles the custom mode in the TOML file <input> and writes the
 resulting MIDI SysEx message to a file.
@@ -32,10 +33,15 @@ By default, if the input filename ends in `.toml`, the output filename
 is obtained by replacing `.toml` with `.syx`. Otherwise, `.syx` is
 appended to the filename.
 
+With `decompile`, <input> is instead a SysEx file previously produced
+by ncc, and an equivalent TOML file is written. The output filename is
+obtained by replacing `.syx` with `.toml` or appending `.toml`.
+
 Options:
-  -o <file>      Write the compiled SysEx to <file>
-  -h, --help     Show this help message
-  -v, --version  Show program version
+  -o <file>          Write the output to <file>
+  -D <name>=<value>  Set the variable <name>, overriding [vars]
+  -h, --help         Show this help message
+  -v, --version      Show program version
 ";
 
 #[derive(Debug)]
@@ -53,7 +59,8 @@ impl<'a> Usage<'a> {
 
 impl Display for Usage<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Usage: {} {}", self.bin, USAGE.trim_end())
+        let usage = USAGE.trim_end().replace("{bin}", self.bin);
+        write!(f, "Usage: {} {usage}", self.bin)
     }
 }
 
@@ -100,6 +107,8 @@ impl PathArg {
 pub struct CompileArgs {
     pub in_path: PathArg,
     pub out_path: PathArg,
+    /// Variables defined with `-D`.
+    pub vars: Vec<(String, String)>,
 }
 
 #[derive(Debug)]
@@ -112,6 +121,8 @@ pub enum Args {
     Version,
     /// Typical usage: compile input, write to output.
     Compile(CompileArgs),
+    /// `decompile` subcommand: decompile input, write to output.
+    Decompile(CompileArgs),
 }
 
 impl Args {
@@ -124,9 +135,11 @@ impl Args {
         Parser {
             args: args.into_iter(),
             options_done: false,
+            decompile: false,
             num_positional: 0,
             in_path: None,
             out_path: None,
+            vars: Vec::new(),
         }
         .parse()
     }
@@ -137,11 +150,13 @@ pub enum ArgsError {
     UnknownShort(char),
     DuplicateShort(char),
     IncompleteShort(char),
+    InvalidDefine(OsString),
     NonAsciiShort(OsString),
     UnknownLong(OsString),
     Unexpected(OsString),
     MissingArgs,
     MissingStdinOutput,
+    DecompileDefine,
 }
 
 impl Display for ArgsError {
@@ -152,6 +167,11 @@ impl Display for ArgsError {
             Self::IncompleteShort(c) => {
                 write!(f, "missing argument for option -{c}")
             }
+            Self::InvalidDefine(s) => write!(
+                f,
+                "invalid argument for option -D: {} (expected <name>=<value>)",
+                s.to_string_lossy(),
+            ),
             Self::NonAsciiShort(s) | Self::UnknownLong(s) => {
                 write!(f, "unknown option: {}", s.to_string_lossy())
             }
@@ -166,6 +186,9 @@ impl Display for ArgsError {
             Self::MissingStdinOutput => {
                 write!(f, "-o must be specified when input is `-`")
             }
+            Self::DecompileDefine => {
+                write!(f, "-D cannot be used with `decompile`")
+            }
         }
     }
 }
@@ -196,9 +219,11 @@ enum NextShort {
 struct Parser<A> {
     args: A,
     options_done: bool,
+    decompile: bool,
     num_positional: usize,
     in_path: Option<PathArg>,
     out_path: Option<PathArg>,
+    vars: Vec<(String, String)>,
 }
 
 impl<A: Iterator<Item = OsString>> Parser<A> {
@@ -217,6 +242,9 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
             return Err(ArgsError::MissingArgs);
         }
         debug_assert_eq!(self.num_positional, Args::NUM_POSITIONAL);
+        if self.decompile && !self.vars.is_empty() {
+            return Err(ArgsError::DecompileDefine);
+        }
         let in_path = self.in_path.unwrap();
         let out_path = if let Some(p) = self.out_path {
             p
@@ -224,18 +252,30 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
             let PathArg::Path(p) = &in_path else {
                 return Err(ArgsError::MissingStdinOutput);
             };
-            PathArg::Path(if p.extension().is_some_and(|x| x == "toml") {
-                p.with_extension("syx")
+            let (from, to) = if self.decompile {
+                ("syx", "toml")
+            } else {
+                ("toml", "syx")
+            };
+            PathArg::Path(if p.extension().is_some_and(|x| x == from) {
+                p.with_extension(to)
             } else {
                 let mut out = p.clone();
-                out.as_mut_os_string().push(".syx");
+                out.as_mut_os_string().push(".");
+                out.as_mut_os_string().push(to);
                 out
             })
         };
-        Ok(Args::Compile(CompileArgs {
+        let args = CompileArgs {
             in_path,
             out_path,
-        }))
+            vars: self.vars,
+        };
+        Ok(if self.decompile {
+            Args::Decompile(args)
+        } else {
+            Args::Compile(args)
+        })
     }
 
     fn to_path<S>(&self, s: S) -> PathArg
@@ -258,6 +298,13 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
             return self.short_opts(arg);
         }
         match self.num_positional {
+            0 if arg == "decompile"
+                && !self.decompile
+                && !self.options_done =>
+            {
+                self.decompile = true;
+                return ControlFlow::Continue(());
+            }
             0 => self.in_path = Some(self.to_path(arg)),
             _ => return ArgsError::Unexpected(arg).into(),
         }
@@ -304,6 +351,22 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
                 self.out_path = Some(self.to_path(arg));
                 ControlFlow::Continue(NextShort::SkipRest)
             }
+            'D' => {
+                let arg = if !rest.is_empty() {
+                    rest.to_owned()
+                } else if let Some(arg) = self.args.next() {
+                    arg
+                } else {
+                    return ArgsError::IncompleteShort(opt).into();
+                };
+                let var = arg.to_str().and_then(|s| s.split_once('='));
+                let Some((name, value)) = var.filter(|v| !v.0.is_empty())
+                else {
+                    return ArgsError::InvalidDefine(arg).into();
+                };
+                self.vars.push((name.to_owned(), value.to_owned()));
+                ControlFlow::Continue(NextShort::SkipRest)
+            }
             'h' => Args::Help.into(),
             'v' => Args::Version.into(),
             _ => ArgsError::UnknownShort(opt).into(),
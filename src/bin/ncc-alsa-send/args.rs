@@ -20,17 +20,32 @@
 use std::ffi::OsString;
 use std::fmt::{self, Display};
 use std::ops::ControlFlow;
+use std::time::Duration;
 
 const USAGE: &str = "\
-<port> <file>
+[options] [<port>] <file>
 
-Sends the MIDI SysEx message in <file> to the ALSA MIDI device on port <port>.
+Sends the MIDI SysEx message in <file> to the ALSA MIDI device on port <port>
+and prints the device's response. <port> is a rawmidi device like hw:1,0,0 or
+a device file like /dev/snd/midiC1D0. If omitted, it is detected from the
+device ID in <file>.
 
 Options:
-  -h, --help     Show this help message
-  -v, --version  Show program version
+  -t, --timeout <secs>  Wait at most <secs> seconds for a response [default: 1]
+  --amidi               Send with `amidi` instead of opening the device
+  --identify            Identify the device on <port> instead of sending a file
+  -h, --help            Show this help message
+  -v, --version         Show program version
+
+Exit status:
+  0  The device responded
+  1  An error occurred
+  2  The device sent an unrecognized reply
+  3  The device didn't respond
 ";
 
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub struct Usage<'a> {
     bin: &'a str,
 }
@@ -54,13 +69,20 @@ pub enum Args {
     Help,
     Version,
     Run {
-        port: OsString,
+        port: Option<OsString>,
         file: OsString,
+        timeout: Duration,
+        amidi: bool,
+    },
+    Identify {
+        port: OsString,
+        timeout: Duration,
+        amidi: bool,
     },
 }
 
 impl Args {
-    pub const NUM_POSITIONAL: usize = 2;
+    pub const MAX_POSITIONAL: usize = 2;
 
     pub fn parse<A>(args: A) -> Result<Self, ArgsError>
     where
@@ -69,9 +91,10 @@ impl Args {
         Parser {
             args: args.into_iter(),
             options_done: false,
-            num_positional: 0,
-            port: None,
-            file: None,
+            positional: Vec::new(),
+            timeout: None,
+            amidi: false,
+            identify: false,
         }
         .parse()
     }
@@ -83,6 +106,10 @@ pub enum ArgsError {
     UnknownLong(OsString),
     Unexpected(OsString),
     MissingArgs,
+    MissingPort,
+    MissingValue(&'static str),
+    InvalidTimeout(OsString),
+    DuplicateTimeout,
 }
 
 impl Display for ArgsError {
@@ -95,11 +122,15 @@ impl Display for ArgsError {
             Self::Unexpected(s) => {
                 write!(f, "unexpected argument: {}", s.to_string_lossy())
             }
-            Self::MissingArgs => write!(
-                f,
-                "missing arguments (expected {})",
-                Args::NUM_POSITIONAL,
-            ),
+            Self::MissingArgs => write!(f, "missing argument: <file>"),
+            Self::MissingPort => write!(f, "missing argument: <port>"),
+            Self::MissingValue(opt) => write!(f, "missing value for {opt}"),
+            Self::InvalidTimeout(s) => {
+                write!(f, "invalid timeout: {}", s.to_string_lossy())
+            }
+            Self::DuplicateTimeout => {
+                write!(f, "timeout specified more than once")
+            }
         }
     }
 }
@@ -121,9 +152,10 @@ impl<C> From<ArgsError> for ControlFlow<ArgsResult, C> {
 struct Parser<A> {
     args: A,
     options_done: bool,
-    num_positional: usize,
-    port: Option<OsString>,
-    file: Option<OsString>,
+    positional: Vec<OsString>,
+    timeout: Option<Duration>,
+    amidi: bool,
+    identify: bool,
 }
 
 impl<A: Iterator<Item = OsString>> Parser<A> {
@@ -138,15 +170,45 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
         if !any {
             return Ok(Args::Empty);
         }
-        if self.num_positional < Args::NUM_POSITIONAL {
-            return Err(ArgsError::MissingArgs);
+        let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        if self.identify {
+            let mut positional = self.positional.into_iter();
+            let port = positional.next().ok_or(ArgsError::MissingPort)?;
+            if let Some(arg) = positional.next() {
+                return Err(ArgsError::Unexpected(arg));
+            }
+            return Ok(Args::Identify {
+                port,
+                timeout,
+                amidi: self.amidi,
+            });
         }
+        let file = self.positional.pop().ok_or(ArgsError::MissingArgs)?;
         Ok(Args::Run {
-            port: self.port.unwrap(),
-            file: self.file.unwrap(),
+            port: self.positional.pop(),
+            file,
+            timeout,
+            amidi: self.amidi,
         })
     }
 
+    fn timeout(&mut self, value: Option<OsString>) -> ControlFlow<ArgsResult> {
+        let Some(value) = value else {
+            return ArgsError::MissingValue("--timeout").into();
+        };
+        if self.timeout.is_some() {
+            return ArgsError::DuplicateTimeout.into();
+        }
+        let secs = value.to_str().and_then(|s| s.parse::<f64>().ok());
+        match secs.and_then(|s| Duration::try_from_secs_f64(s).ok()) {
+            Some(t) => {
+                self.timeout = Some(t);
+                ControlFlow::Continue(())
+            }
+            None => ArgsError::InvalidTimeout(value).into(),
+        }
+    }
+
     fn arg(&mut self, arg: OsString) -> ControlFlow<ArgsResult> {
         let bytes = arg.as_encoded_bytes();
         if self.options_done || arg == "-" {
@@ -155,12 +217,10 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
         } else if bytes.starts_with(b"-") {
             return self.short_opts(arg);
         }
-        match self.num_positional {
-            0 => self.port = Some(arg),
-            1 => self.file = Some(arg),
-            _ => return ArgsError::Unexpected(arg).into(),
+        if self.positional.len() == Args::MAX_POSITIONAL {
+            return ArgsError::Unexpected(arg).into();
         }
-        self.num_positional += 1;
+        self.positional.push(arg);
         ControlFlow::Continue(())
     }
 
@@ -173,6 +233,14 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
             match char::from(b) {
                 'h' => Args::Help.into(),
                 'v' => Args::Version.into(),
+                't' => {
+                    let value = match opts.to_str().map(|s| &s[2..]) {
+                        Some("") => self.args.next(),
+                        Some(rest) => Some(rest.into()),
+                        None => return ArgsError::InvalidTimeout(opts).into(),
+                    };
+                    self.timeout(value)
+                }
                 c => ArgsError::UnknownShort(c).into(),
             }
         } else {
@@ -188,7 +256,23 @@ impl<A: Iterator<Item = OsString>> Parser<A> {
             }
             "--help" => Args::Help.into(),
             "--version" => Args::Version.into(),
-            _ => ArgsError::UnknownLong(opt).into(),
+            "--amidi" => {
+                self.amidi = true;
+                ControlFlow::Continue(())
+            }
+            "--identify" => {
+                self.identify = true;
+                ControlFlow::Continue(())
+            }
+            "--timeout" => {
+                let value = self.args.next();
+                self.timeout(value)
+            }
+            _ => match opt.to_str().and_then(|s| s.strip_prefix("--timeout="))
+            {
+                Some(value) => self.timeout(Some(value.into())),
+                None => ArgsError::UnknownLong(opt).into(),
+            },
         }
     }
 }
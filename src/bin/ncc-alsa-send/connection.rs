@@ -0,0 +1,115 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[cfg(target_os = "linux")]
+use super::rawmidi;
+use super::reply::{self, Hex};
+use super::run_verbose;
+use std::ffi::{OsStr, OsString};
+use std::time::Duration;
+
+/// A connection to a MIDI port, either opened directly or through `amidi`.
+pub enum Connection {
+    #[cfg(target_os = "linux")]
+    Native {
+        name: rawmidi::PortName,
+        port: rawmidi::Port,
+    },
+    Amidi(OsString),
+}
+
+impl Connection {
+    /// Connects to `port`. `amidi` is used if requested or if the port
+    /// can't be opened directly.
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub fn open(port: &OsStr, amidi: bool) -> Result<Self, ()> {
+        #[cfg(target_os = "linux")]
+        if let Some(name) = rawmidi::PortName::parse(port).filter(|_| !amidi) {
+            let port = rawmidi::Port::open(&name).map_err(|e| {
+                eprintln!("error: could not open {name}: {e}");
+                eprintln!("To send with `amidi` instead, use --amidi.");
+            })?;
+            return Ok(Self::Native {
+                name,
+                port,
+            });
+        }
+        Ok(Self::Amidi(port.into()))
+    }
+
+    /// Sends `data` and returns the data received in response, if any.
+    /// `file`, if provided, must contain `data`; `amidi` sends it from
+    /// there.
+    pub fn exchange(
+        &mut self,
+        data: &[u8],
+        file: Option<&OsStr>,
+        timeout: Duration,
+    ) -> Result<Option<Vec<u8>>, ()> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Native {
+                name,
+                port,
+            } => {
+                println!("> sending {} bytes to {name}", data.len());
+                port.send(data, timeout).map_err(|e| {
+                    eprintln!("error: could not send to {name}: {e}");
+                })?;
+                let received = port.receive(timeout).map_err(|e| {
+                    eprintln!("error: could not read from {name}: {e}");
+                })?;
+                if let Some(received) = &received {
+                    println!("{}", Hex(received));
+                }
+                Ok(received)
+            }
+            Self::Amidi(port) => {
+                let timeout = format!("-t{}", timeout.as_secs_f64());
+                let port: &OsStr = port;
+                let output = if let Some(file) = file {
+                    run_verbose("amidi", [
+                        "-p".as_ref(),
+                        port,
+                        "-s".as_ref(),
+                        file,
+                    ])?;
+                    run_verbose("amidi", [
+                        "-p".as_ref(),
+                        port,
+                        "-d".as_ref(),
+                        timeout.as_ref(),
+                    ])?
+                } else {
+                    let hex = Hex(data).to_string();
+                    run_verbose("amidi", [
+                        "-p".as_ref(),
+                        port,
+                        "-S".as_ref(),
+                        hex.as_ref(),
+                        "-d".as_ref(),
+                        timeout.as_ref(),
+                    ])?
+                };
+                let received = reply::parse_hex(&output);
+                Ok((!received.is_empty()).then_some(received))
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Families of Novation devices, as identified by the device ID in custom
+//! mode SysEx messages.
+
+use crate::reply::Firmware;
+use ncc::launchkey::{flkey, flkey_mini, launchkey_mini_mk3, launchkey_mk3};
+use ncc::launchpad::{launchpad_mini_mk3, launchpad_x};
+
+/// A family of devices that share a custom mode format.
+#[derive(Debug)]
+pub struct Family {
+    /// The device ID used in custom mode SysEx messages.
+    pub device_id: u8,
+    /// The name of the family, as it appears (possibly followed by a model
+    /// name) in the names of the device's MIDI ports.
+    pub name: &'static str,
+    /// The family code in the device's reply to a Universal Device Inquiry,
+    /// if known.
+    pub inquiry_code: Option<u16>,
+    /// The oldest firmware version known to work with custom modes from
+    /// ncc, if known.
+    pub known_good: Option<Firmware>,
+}
+
+const FAMILIES: &[Family] = &[
+    Family {
+        device_id: launchkey_mini_mk3::DEVICE_ID,
+        name: "Launchkey Mini MK3",
+        inquiry_code: Some(0x0135),
+        known_good: Some(Firmware([0, 1, 1, 1])),
+    },
+    Family {
+        device_id: launchkey_mk3::DEVICE_ID,
+        name: "Launchkey MK3",
+        inquiry_code: Some(0x0134),
+        known_good: None,
+    },
+    Family {
+        device_id: launchpad_mini_mk3::DEVICE_ID,
+        name: "Launchpad Mini MK3",
+        inquiry_code: Some(0x0113),
+        known_good: None,
+    },
+    Family {
+        device_id: launchpad_x::DEVICE_ID,
+        name: "Launchpad X",
+        inquiry_code: Some(0x0103),
+        known_good: Some(Firmware([0, 2, 0, 1])),
+    },
+    Family {
+        device_id: flkey_mini::DEVICE_ID,
+        name: "FLkey Mini",
+        inquiry_code: None,
+        known_good: None,
+    },
+    Family {
+        device_id: flkey::DEVICE_ID,
+        name: "FLkey",
+        inquiry_code: None,
+        known_good: None,
+    },
+];
+
+impl Family {
+    pub fn from_device_id(device_id: u8) -> Option<&'static Self> {
+        FAMILIES.iter().find(|f| f.device_id == device_id)
+    }
+
+    pub fn from_inquiry_code(code: u16) -> Option<&'static Self> {
+        FAMILIES.iter().find(|f| f.inquiry_code == Some(code))
+    }
+
+    /// Whether a MIDI port named `port_name` belongs to a device in this
+    /// family.
+    pub fn matches(&self, port_name: &str) -> bool {
+        // "FLkey" shouldn't match "FLkey Mini", for example.
+        let is_mini = |s: &str| s.split(' ').any(|w| w == "Mini");
+        port_name.contains(self.name)
+            && is_mini(port_name) == is_mini(self.name)
+    }
+}
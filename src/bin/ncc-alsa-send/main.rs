@@ -17,14 +17,32 @@
  * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::ffi::OsStr;
+#![deny(unsafe_op_in_unsafe_fn)]
+#![warn(clippy::undocumented_unsafe_blocks)]
+
+use std::ffi::{OsStr, OsString};
 use std::fs::{self, File};
 use std::io::{self, BufWriter, Read, Write};
 use std::path::Path;
-use std::process::{Command, ExitCode};
+use std::process::{Command, ExitCode, Stdio};
+use std::ptr;
+use std::time::Duration;
 
 mod args;
+mod connection;
+mod family;
+#[cfg(target_os = "linux")]
+mod rawmidi;
+mod reply;
 use args::{Args, Usage};
+use connection::Connection;
+use family::Family;
+use reply::{Expected, Firmware, Identity, Outcome};
+
+/// Exit status when the device's response isn't recognized.
+const EXIT_UNRECOGNIZED: u8 = 2;
+/// Exit status when the device doesn't respond.
+const EXIT_TIMEOUT: u8 = 3;
 
 fn must_quote(b: u8) -> bool {
     !matches!(
@@ -58,7 +76,9 @@ where
     writer.write_all(b"\"")
 }
 
-fn run_verbose<P, A, T>(program: P, args: A) -> Result<(), ()>
+/// Runs a program after printing its command line. Its standard output is
+/// printed and returned.
+fn run_verbose<P, A, T>(program: P, args: A) -> Result<Vec<u8>, ()>
 where
     P: AsRef<OsStr>,
     A: Copy + IntoIterator<Item = T>,
@@ -79,11 +99,19 @@ where
         eprintln!("error writing to stdout: {e}");
     })?;
 
-    let status = Command::new(program).args(args).status().map_err(|e| {
-        eprintln!("error running `{}`: {e}", program.to_string_lossy());
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| {
+            eprintln!("error running `{}`: {e}", program.to_string_lossy());
+        })?;
+    io::stdout().write_all(&output.stdout).map_err(|e| {
+        eprintln!("error writing to stdout: {e}");
     })?;
+    let status = output.status;
     if status.success() {
-        return Ok(());
+        return Ok(output.stdout);
     }
     let program_str = program.to_string_lossy();
     match status.code() {
@@ -120,12 +148,13 @@ fn ensure_syx<P: AsRef<Path>>(path: P) -> Result<(), ()> {
             return Ok(());
         }
     }
-    match File::open(path).and_then(|f| f.bytes().next().transpose()) {
+    let mut first = [0; 1];
+    match File::open(path).and_then(|mut f| f.read(&mut first)) {
         Err(e) => {
             eprintln!("error: could not read `{}`: {e}", path.display());
             Err(())
         }
-        Ok(Some(0xf0)) => Ok(()),
+        Ok(1) if first[0] == 0xf0 => Ok(()),
         Ok(_) => {
             eprintln!("error: not a SysEx file: {}", path.display());
             Err(())
@@ -133,7 +162,7 @@ fn ensure_syx<P: AsRef<Path>>(path: P) -> Result<(), ()> {
     }
 }
 
-fn run() -> Result<(), ()> {
+fn run() -> Result<Outcome, ()> {
     let mut args = std::env::args_os();
     let arg0 = args.next();
     let bin = arg0
@@ -146,38 +175,209 @@ fn run() -> Result<(), ()> {
         eprintln!("error: {e}");
         eprintln!("See `{bin} --help` for usage information.");
     })?;
-    let (port, file) = match args {
+    let (port, file, timeout, amidi) = match args {
         Args::Empty => {
             eprintln!("{usage}");
             return Err(());
         }
         Args::Help => {
             println!("{usage}");
-            return Ok(());
+            return Ok(Outcome::Responded);
         }
         Args::Version => {
             println!("ncc-alsa-send {}", env!("CARGO_PKG_VERSION"));
-            return Ok(());
+            return Ok(Outcome::Responded);
+        }
+        Args::Identify {
+            port,
+            timeout,
+            amidi,
+        } => {
+            let mut conn = Connection::open(&port, amidi)?;
+            return identify(&mut conn, timeout);
         }
         Args::Run {
             port,
             file,
-        } => (port, file),
+            timeout,
+            amidi,
+        } => (port, file, timeout, amidi),
     };
 
     ensure_syx(&file)?;
-    run_verbose("amidi", ["-p".as_ref(), &*port, "-s".as_ref(), &*file])?;
-    run_verbose("amidi", [
-        "-p".as_ref(),
-        &*port,
-        "-d".as_ref(),
-        "-t1".as_ref(),
-    ])
+    let path = Path::new(&file);
+    let data = fs::read(path).map_err(|e| {
+        eprintln!("error: could not read `{}`: {e}", path.display());
+    })?;
+    let port = match port {
+        Some(port) => port,
+        None => detect_port(&data)?,
+    };
+    let mut conn = Connection::open(&port, amidi)?;
+    let expected = Expected::from_syx(&data);
+    if let Some(family) =
+        expected.and_then(|e| Family::from_device_id(e.device_id()))
+    {
+        check_device(&mut conn, family, timeout)?;
+    }
+    let received = conn.exchange(&data, Some(&file), timeout)?;
+    let Some(expected) = expected else {
+        eprintln!(
+            "warning: not a Novation custom mode; the response was not \
+            checked",
+        );
+        return Ok(Outcome::Responded);
+    };
+    let outcome = expected.check(received.as_deref());
+    match outcome {
+        Outcome::Responded => println!("The device responded."),
+        Outcome::Unrecognized => eprintln!(
+            "error: unrecognized response; expected a SysEx message from a \
+            device with ID 0x{:02x}",
+            expected.device_id(),
+        ),
+        Outcome::Timeout => {
+            eprintln!(
+                "error: no response from device within {} s",
+                timeout.as_secs_f64(),
+            );
+            eprintln!(
+                "If the device is in custom mode, its firmware may need to \
+                be updated.",
+            );
+        }
+    }
+    Ok(outcome)
+}
+
+/// Sends a Universal Device Inquiry and returns the device's identity, or
+/// [`None`] if the device didn't reply or isn't a Novation device.
+fn inquire(
+    conn: &mut Connection,
+    timeout: Duration,
+) -> Result<Option<Identity>, ()> {
+    let received = conn.exchange(reply::INQUIRY, None, timeout)?;
+    Ok(received.as_deref().and_then(Identity::parse))
+}
+
+fn warn_if_old(family: &Family, firmware: Firmware) {
+    if let Some(known_good) = family.known_good.filter(|&v| firmware < v) {
+        eprintln!(
+            "warning: firmware {firmware} is older than {known_good}, the \
+            oldest version known to work; custom modes may not take effect",
+        );
+    }
+}
+
+fn identify(conn: &mut Connection, timeout: Duration) -> Result<Outcome, ()> {
+    let Some(identity) = inquire(conn, timeout)? else {
+        eprintln!("error: no reply from a Novation device");
+        return Ok(Outcome::Timeout);
+    };
+    let family = Family::from_inquiry_code(identity.family);
+    match family {
+        Some(family) => {
+            println!("Family: {} (0x{:04x})", family.name, identity.family);
+        }
+        None => println!("Family: unknown (0x{:04x})", identity.family),
+    }
+    println!("Model: 0x{:04x}", identity.model);
+    println!("Firmware: {}", identity.firmware);
+    if let Some(family) = family {
+        warn_if_old(family, identity.firmware);
+    }
+    Ok(Outcome::Responded)
+}
+
+/// Makes sure the device on `conn` belongs to `family` before a custom mode
+/// is sent to it.
+fn check_device(
+    conn: &mut Connection,
+    family: &Family,
+    timeout: Duration,
+) -> Result<(), ()> {
+    let Some(identity) = inquire(conn, timeout)? else {
+        eprintln!("warning: could not identify the device; sending anyway");
+        return Ok(());
+    };
+    match Family::from_inquiry_code(identity.family) {
+        Some(actual) if !ptr::eq(actual, family) => {
+            eprintln!(
+                "error: this custom mode is for a {}, but the device is a {}",
+                family.name, actual.name,
+            );
+            return Err(());
+        }
+        None if family.inquiry_code.is_some() => {
+            eprintln!(
+                "error: this custom mode is for a {}, but the device is not \
+                (family code 0x{:04x})",
+                family.name, identity.family,
+            );
+            return Err(());
+        }
+        _ => {}
+    }
+    warn_if_old(family, identity.firmware);
+    Ok(())
+}
+
+/// Finds the port of the device that `data` is meant for.
+#[cfg(target_os = "linux")]
+fn detect_port(data: &[u8]) -> Result<OsString, ()> {
+    let family = Expected::from_syx(data)
+        .and_then(|e| Family::from_device_id(e.device_id()));
+    let Some(family) = family else {
+        eprintln!(
+            "error: could not determine which device this file is for; \
+            please specify <port>",
+        );
+        return Err(());
+    };
+    let ports = rawmidi::list_ports().map_err(|e| {
+        eprintln!("error: could not list MIDI ports: {e}");
+    })?;
+    let mut candidates: Vec<_> =
+        ports.iter().filter(|p| family.matches(&p.name)).collect();
+    // Custom modes are sent to the first subdevice, the device's main MIDI
+    // port. Others, like the DAW port of a Launchkey, serve other purposes.
+    candidates.dedup_by_key(|p| (p.card, p.device));
+    match candidates[..] {
+        [port] => {
+            println!("Using port {port}");
+            Ok(port.port_name().to_string().into())
+        }
+        [] => {
+            eprintln!("error: no {} found", family.name);
+            if !ports.is_empty() {
+                eprintln!("Available ports:");
+                ports.iter().for_each(|p| eprintln!("  {p}"));
+            }
+            Err(())
+        }
+        _ => {
+            eprintln!(
+                "error: found multiple {} devices; please specify one of \
+                these ports:",
+                family.name,
+            );
+            candidates.iter().for_each(|p| eprintln!("  {p}"));
+            Err(())
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_port(_data: &[u8]) -> Result<OsString, ()> {
+    eprintln!("error: <port> must be specified on this system");
+    Err(())
 }
 
 fn main() -> ExitCode {
     match run() {
-        Ok(()) => ExitCode::SUCCESS,
+        Ok(Outcome::Responded) => ExitCode::SUCCESS,
+        Ok(Outcome::Unrecognized) => ExitCode::from(EXIT_UNRECOGNIZED),
+        Ok(Outcome::Timeout) => ExitCode::from(EXIT_TIMEOUT),
         Err(()) => ExitCode::FAILURE,
     }
 }
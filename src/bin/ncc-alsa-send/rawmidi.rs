@@ -0,0 +1,312 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Direct access to ALSA rawmidi devices through `/dev/snd`, without
+//! alsa-lib or `amidi`.
+
+use std::ffi::{OsStr, c_int};
+use std::fmt::{self, Display};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// `SNDRV_CTL_IOCTL_RAWMIDI_PREFER_SUBDEVICE` from `<sound/asound.h>`.
+const PREFER_SUBDEVICE: libc::Ioctl = libc::_IOW::<c_int>(b'U' as u32, 0x42);
+
+/// A rawmidi device, as given on the command line.
+#[derive(Clone, Debug)]
+pub enum PortName {
+    /// `hw:<card>,<device>[,<subdevice>]`. `card` may be a card number or
+    /// the card's ID, like `amidi` accepts.
+    Hw {
+        card: String,
+        device: u32,
+        subdevice: Option<u32>,
+    },
+    /// A path to a device file, like `/dev/snd/midiC1D0`.
+    Path(PathBuf),
+}
+
+impl PortName {
+    /// Parses a port name, returning [`None`] if it isn't a form that can be
+    /// opened directly (in which case `amidi` may still understand it).
+    pub fn parse(port: &OsStr) -> Option<Self> {
+        if port.as_encoded_bytes().starts_with(b"/") {
+            return Some(Self::Path(port.into()));
+        }
+        let mut parts = port.to_str()?.strip_prefix("hw:")?.split(',');
+        let card = parts.next().filter(|c| !c.is_empty())?.to_owned();
+        let device = parts.next().map_or(Some(0), |d| d.parse().ok())?;
+        let subdevice = parts.next().map(str::parse).transpose().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::Hw {
+            card,
+            device,
+            subdevice,
+        })
+    }
+
+    /// Resolves the card number, looking up the card's ID in
+    /// `/proc/asound` if necessary.
+    fn card_number(card: &str) -> io::Result<u32> {
+        if let Ok(n) = card.parse() {
+            return Ok(n);
+        }
+        let link = fs::read_link(format!("/proc/asound/{card}"))
+            .map_err(|e| with_context(e, &format!("unknown card `{card}`")))?;
+        link.to_str()
+            .and_then(|s| s.strip_prefix("card"))
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected link target for card `{card}`"),
+                )
+            })
+    }
+}
+
+impl Display for PortName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hw {
+                card,
+                device,
+                subdevice,
+            } => {
+                write!(f, "hw:{card},{device}")?;
+                if let Some(sub) = subdevice {
+                    write!(f, ",{sub}")?;
+                }
+                Ok(())
+            }
+            Self::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+fn with_context(e: io::Error, context: &str) -> io::Error {
+    io::Error::new(e.kind(), format!("{context}: {e}"))
+}
+
+/// Waits until `file` is ready for `events`, or `timeout` elapses. Returns
+/// whether the file is ready.
+fn wait(
+    file: &File,
+    events: libc::c_short,
+    timeout: Duration,
+) -> io::Result<bool> {
+    let mut pfd = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events,
+        revents: 0,
+    };
+    let ms = c_int::try_from(timeout.as_millis()).unwrap_or(c_int::MAX);
+    // SAFETY: `pfd` is a valid `pollfd`, and we pass a count of 1.
+    match unsafe { libc::poll(&mut pfd, 1, ms) } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(false),
+        _ if pfd.revents & (libc::POLLERR | libc::POLLHUP) != 0 => Err(
+            io::Error::new(io::ErrorKind::BrokenPipe, "device disconnected"),
+        ),
+        _ => Ok(true),
+    }
+}
+
+/// An open rawmidi device.
+pub struct Port {
+    file: File,
+}
+
+impl Port {
+    pub fn open(name: &PortName) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        // Without `O_NONBLOCK`, opening a busy device waits until it's
+        // released instead of failing.
+        options.read(true).write(true).custom_flags(libc::O_NONBLOCK);
+        let (card, device, subdevice) = match name {
+            PortName::Path(path) => {
+                let file = options.open(path)?;
+                return Ok(Self {
+                    file,
+                });
+            }
+            PortName::Hw {
+                card,
+                device,
+                subdevice,
+            } => (PortName::card_number(card)?, *device, *subdevice),
+        };
+
+        // As in alsa-lib, the subdevice is selected through the card's
+        // control device, which must stay open while the rawmidi device is
+        // opened.
+        let _control = subdevice
+            .map(|sub| -> io::Result<_> {
+                let control = File::open(format!("/dev/snd/controlC{card}"))?;
+                let sub = c_int::try_from(sub).unwrap_or(c_int::MAX);
+                // SAFETY: This ioctl takes a pointer to a `c_int`, which
+                // lives for the duration of the call.
+                let ret = unsafe {
+                    libc::ioctl(
+                        control.as_raw_fd(),
+                        PREFER_SUBDEVICE,
+                        &sub as *const c_int,
+                    )
+                };
+                if ret == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(control)
+            })
+            .transpose()?;
+        let file = options.open(format!("/dev/snd/midiC{card}D{device}"))?;
+        Ok(Self {
+            file,
+        })
+    }
+
+    /// Writes all of `data` to the device, waiting at most `timeout` for
+    /// the device to accept each chunk.
+    pub fn send(
+        &mut self,
+        mut data: &[u8],
+        timeout: Duration,
+    ) -> io::Result<()> {
+        while !data.is_empty() {
+            match self.file.write(data) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => data = &data[n..],
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if !wait(&self.file, libc::POLLOUT, timeout)? {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "timed out while sending",
+                        ));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads from the device until a complete SysEx message has been
+    /// received, `timeout` elapses, or the device reaches end-of-file.
+    /// Returns all bytes read, or [`None`] if nothing was received. Partial
+    /// data is returned as-is if the message isn't finished in time.
+    pub fn receive(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<Vec<u8>>> {
+        let deadline = Instant::now() + timeout;
+        let mut received = Vec::new();
+        let mut buf = [0; 256];
+        loop {
+            if let Some(start) = received.iter().position(|&b| b == 0xf0) {
+                if received[start..].contains(&0xf7) {
+                    return Ok(Some(received));
+                }
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !wait(&self.file, libc::POLLIN, remaining)? {
+                return Ok((!received.is_empty()).then_some(received));
+            }
+            match self.file.read(&mut buf) {
+                Ok(0) => return Ok((!received.is_empty()).then_some(received)),
+                Ok(n) => received.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// An output subdevice of a rawmidi device found on the system.
+#[derive(Clone, Debug)]
+pub struct PortInfo {
+    pub card: u32,
+    pub device: u32,
+    /// The subdevice, or [`None`] if the subdevices couldn't be listed.
+    pub subdevice: Option<u32>,
+    /// The name of the device, or an empty string if unknown.
+    pub name: String,
+}
+
+impl PortInfo {
+    pub fn port_name(&self) -> PortName {
+        PortName::Hw {
+            card: self.card.to_string(),
+            device: self.device,
+            subdevice: self.subdevice,
+        }
+    }
+}
+
+impl Display for PortInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}  {}", self.port_name(), self.name)
+    }
+}
+
+/// Lists the output subdevices of the rawmidi devices in `/dev/snd`, sorted
+/// by card, device, and subdevice. Names and subdevices are read from
+/// `/proc/asound`.
+pub fn list_ports() -> io::Result<Vec<PortInfo>> {
+    let mut ports = Vec::new();
+    for entry in fs::read_dir("/dev/snd")? {
+        let entry = entry?;
+        let Some((card, device)) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.strip_prefix("midiC"))
+            .and_then(|s| s.split_once('D'))
+            .and_then(|(c, d)| Some((c.parse().ok()?, d.parse().ok()?)))
+        else {
+            continue;
+        };
+        let info = format!("/proc/asound/card{card}/midi{device}");
+        let info = fs::read_to_string(info).unwrap_or_default();
+        let name = info.lines().next().unwrap_or_default().trim();
+        // Each output subdevice has a line like `Output 0`.
+        let mut subdevices: Vec<_> = info
+            .lines()
+            .filter_map(|line| line.strip_prefix("Output ")?.parse().ok())
+            .map(Some)
+            .collect();
+        if subdevices.is_empty() {
+            subdevices.push(None);
+        }
+        ports.extend(subdevices.into_iter().map(|subdevice| PortInfo {
+            card,
+            device,
+            subdevice,
+            name: name.to_owned(),
+        }));
+    }
+    ports.sort_by_key(|p| (p.card, p.device, p.subdevice));
+    Ok(ports)
+}
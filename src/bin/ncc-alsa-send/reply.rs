@@ -0,0 +1,172 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Checking the device's response to a custom mode.
+
+use std::fmt::{self, Display};
+
+/// The start of every SysEx message to or from a Novation device, up to
+/// (but not including) the device ID.
+const NOVATION_PREFIX: &[u8] = b"\xf0\x00\x20\x29\x02";
+
+/// The device that should reply after a custom mode is sent.
+///
+/// What Novation devices send in reply to a custom mode hasn't been
+/// established from a capture, so the reply is checked only for coming from
+/// the right kind of device; in particular, it can't tell whether the device
+/// accepted the custom mode.
+#[derive(Clone, Copy, Debug)]
+pub struct Expected {
+    device_id: u8,
+}
+
+impl Expected {
+    /// Determines the expected reply from the SysEx data that was sent.
+    /// Returns [`None`] if the data isn't a Novation custom mode.
+    pub fn from_syx(syx: &[u8]) -> Option<Self> {
+        syx.strip_prefix(NOVATION_PREFIX).and_then(|rest| {
+            Some(Self {
+                device_id: *rest.first()?,
+            })
+        })
+    }
+
+    pub fn device_id(self) -> u8 {
+        self.device_id
+    }
+
+    /// Checks the data received from the device, which is [`None`] if
+    /// nothing was received. Real-time messages are ignored.
+    pub fn check(self, received: Option<&[u8]>) -> Outcome {
+        let Some(received) = received.filter(|r| !is_realtime(r)) else {
+            return Outcome::Timeout;
+        };
+        let from_device = sysex_messages(received).any(|msg| {
+            msg.strip_prefix(NOVATION_PREFIX)
+                .is_some_and(|rest| rest.first() == Some(&self.device_id))
+        });
+        if from_device {
+            Outcome::Responded
+        } else {
+            Outcome::Unrecognized
+        }
+    }
+}
+
+/// The result of sending a custom mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The device replied with a SysEx message of its own.
+    Responded,
+    /// Something was received, but not a SysEx message from the device.
+    Unrecognized,
+    /// The device didn't reply.
+    Timeout,
+}
+
+/// The MIDI Universal Device Inquiry message.
+pub const INQUIRY: &[u8] = b"\xf0\x7e\x7f\x06\x01\xf7";
+
+/// A firmware version, as four decimal digits. The first two form the major
+/// version, so `[0, 2, 0, 1]` is version 2.0.1.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Firmware(pub [u8; 4]);
+
+impl Display for Firmware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{}.{c}.{d}", u16::from(a) * 10 + u16::from(b))
+    }
+}
+
+/// A Novation device's reply to [`INQUIRY`].
+#[derive(Clone, Copy, Debug)]
+pub struct Identity {
+    pub family: u16,
+    pub model: u16,
+    pub firmware: Firmware,
+}
+
+impl Identity {
+    /// Finds a reply from a Novation device in `received`.
+    pub fn parse(received: &[u8]) -> Option<Self> {
+        sysex_messages(received).find_map(Self::parse_message)
+    }
+
+    fn parse_message(msg: &[u8]) -> Option<Self> {
+        let rest = msg.strip_prefix(b"\xf0\x7e")?;
+        let [_, 0x06, 0x02, 0x00, 0x20, 0x29, f0, f1, m0, m1, v @ ..] = rest
+        else {
+            return None;
+        };
+        let [v0, v1, v2, v3, 0xf7] = *v else {
+            return None;
+        };
+        Some(Self {
+            family: u16::from_le_bytes([*f0, *f1]),
+            model: u16::from_le_bytes([*m0, *m1]),
+            firmware: Firmware([v0, v1, v2, v3]),
+        })
+    }
+}
+
+/// Returns the complete SysEx messages in `data`, in order.
+pub fn sysex_messages(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let start = rest.iter().position(|&b| b == 0xf0)?;
+        let len = rest[start..].iter().position(|&b| b == 0xf7)?;
+        let msg = &rest[start..=start + len];
+        rest = &rest[start + len + 1..];
+        Some(msg)
+    })
+}
+
+/// Whether `data` consists only of real-time messages, like timing clock
+/// and active sensing, which aren't replies to anything.
+fn is_realtime(data: &[u8]) -> bool {
+    data.iter().all(|&b| b >= 0xf8)
+}
+
+/// Parses the output of `amidi --dump`, which consists of bytes in
+/// hexadecimal separated by whitespace. Anything else is ignored.
+pub fn parse_hex(output: &[u8]) -> Vec<u8> {
+    output
+        .split(u8::is_ascii_whitespace)
+        .filter_map(|word| {
+            let word = std::str::from_utf8(word).ok()?;
+            (word.len() == 2).then(|| u8::from_str_radix(word, 16).ok())?
+        })
+        .collect()
+}
+
+/// Formats bytes in hexadecimal, like `amidi --dump`.
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{b:02X}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The 128-color palette shared by Novation devices, and names for some of
+//! its colors.
+
+use super::common::MidiValue;
+
+/// The sRGB values of the palette, as `0xRRGGBB`, in order of color number.
+/// These are the colors listed in `doc/colors.html`.
+const PALETTE: [u32; 128] = [
+    0x616161,
+    0xb3b3b3,
+    0xdddddd,
+    0xffffff,
+    0xffb3b3,
+    0xff6161,
+    0xdd6161,
+    0xb36161,
+    0xfff3d5,
+    0xffb361,
+    0xdd8c61,
+    0xb37661,
+    0xffeea1,
+    0xffff61,
+    0xdddd61,
+    0xb3b361,
+    0xddffa1,
+    0xc2ff61,
+    0xa1dd61,
+    0x81b361,
+    0xc2ffb3,
+    0x61ff61,
+    0x61dd61,
+    0x61b361,
+    0xc2ffc2,
+    0x61ff8c,
+    0x61dd76,
+    0x61b36b,
+    0xc2ffcc,
+    0x61ffcc,
+    0x61dda1,
+    0x61b381,
+    0xc2fff3,
+    0x61ffe9,
+    0x61ddc2,
+    0x61b396,
+    0xc2f3ff,
+    0x61eeff,
+    0x61c7dd,
+    0x61a1b3,
+    0xc2ddff,
+    0x61c7ff,
+    0x61a1dd,
+    0x6181b3,
+    0xa18cff,
+    0x6161ff,
+    0x6161dd,
+    0x6161b3,
+    0xccb3ff,
+    0xa161ff,
+    0x8161dd,
+    0x7661b3,
+    0xffb3ff,
+    0xff61ff,
+    0xdd61dd,
+    0xb361b3,
+    0xffb3d5,
+    0xff61c2,
+    0xdd61a1,
+    0xb3618c,
+    0xff7661,
+    0xe9b361,
+    0xddc261,
+    0xa1a161,
+    0x61b361,
+    0x61b38c,
+    0x618cd5,
+    0x6161ff,
+    0x61b3b3,
+    0x8c61f3,
+    0xccb3c2,
+    0x8c7681,
+    0xff6161,
+    0xf3ffa1,
+    0xeefc61,
+    0xccff61,
+    0x76dd61,
+    0x61ffcc,
+    0x61e9ff,
+    0x61a1ff,
+    0x8c61ff,
+    0xcc61fc,
+    0xee8cdd,
+    0xa17661,
+    0xffa161,
+    0xddf961,
+    0xd5ff8c,
+    0x61ff61,
+    0xb3ffa1,
+    0xccfcd5,
+    0xb3fff6,
+    0xcce4ff,
+    0xa1c2f6,
+    0xd5c2f9,
+    0xf98cff,
+    0xff61cc,
+    0xffc261,
+    0xf3ee61,
+    0xe4ff61,
+    0xddcc61,
+    0xb3a161,
+    0x61ba76,
+    0x76c28c,
+    0x8181a1,
+    0x818ccc,
+    0xccaa81,
+    0xdd6161,
+    0xf9b3a1,
+    0xf9ba76,
+    0xfff38c,
+    0xe9f9a1,
+    0xd5ee76,
+    0x8181a1,
+    0xf9f9d5,
+    0xddfce4,
+    0xe9e9ff,
+    0xe4d5ff,
+    0xb3b3b3,
+    0xd5d5d5,
+    0xf9ffff,
+    0xe96161,
+    0xaa6161,
+    0x81f661,
+    0x61b361,
+    0xf3ee61,
+    0xb3a161,
+    0xeec261,
+    0xc27661,
+];
+
+/// Names for some of the colors in the palette, and their numbers.
+const NAMES: [(&str, u8); 17] = [
+    ("off", 0),
+    ("gray", 1),
+    ("grey", 1),
+    ("white", 3),
+    ("red", 5),
+    ("orange", 9),
+    ("yellow", 13),
+    ("lime", 17),
+    ("green", 21),
+    ("spring-green", 25),
+    ("cyan", 37),
+    ("sky-blue", 41),
+    ("blue", 45),
+    ("purple", 49),
+    ("magenta", 53),
+    ("pink", 57),
+    ("amber", 96),
+];
+
+/// Gets the number of the color `name`, like `red`.
+pub fn named(name: &str) -> Option<u8> {
+    NAMES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+}
+
+/// The names of the colors.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    NAMES.into_iter().map(|(name, _)| name)
+}
+
+/// Parses a hex color like `#ff8800` into its red, green, and blue
+/// components.
+pub fn parse_hex(s: &str) -> Option<[u8; 3]> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(rgb(value))
+}
+
+/// Splits `0xRRGGBB` into its components.
+fn rgb(value: u32) -> [u8; 3] {
+    let [_, r, g, b] = value.to_be_bytes();
+    [r, g, b]
+}
+
+/// Formats a color from the palette as hex, like `#ff6161`.
+pub fn hex(color: MidiValue) -> String {
+    format!("#{:06x}", PALETTE[usize::from(color.value())])
+}
+
+/// Finds the color in the palette closest to `rgb`, as perceived, and the
+/// CIEDE2000 color difference between them: around 2 is barely noticeable,
+/// and 10 or more is plainly a different color.
+pub fn nearest(rgb: [u8; 3]) -> (MidiValue, f64) {
+    let target = Lab::from_rgb(rgb);
+    (0..=127)
+        .filter_map(MidiValue::new)
+        .zip(PALETTE)
+        .map(|(color, c)| {
+            (color, target.difference(&Lab::from_rgb(self::rgb(c))))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("palette should not be empty")
+}
+
+/// A color in the CIELAB color space, under the D65 illuminant.
+struct Lab {
+    l: f64,
+    a: f64,
+    b: f64,
+}
+
+impl Lab {
+    fn from_rgb(rgb: [u8; 3]) -> Self {
+        /// Converts an sRGB component to linear RGB.
+        fn linear(value: u8) -> f64 {
+            let value = f64::from(value) / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        /// The nonlinear function used to convert from XYZ to CIELAB.
+        fn f(value: f64) -> f64 {
+            const SIGMA: f64 = 6.0 / 29.0;
+            if value > SIGMA.powi(3) {
+                value.cbrt()
+            } else {
+                value / (3.0 * SIGMA.powi(2)) + 4.0 / 29.0
+            }
+        }
+
+        let [r, g, b] = rgb.map(linear);
+        let x = r * 0.4124 + g * 0.3576 + b * 0.1805;
+        let y = r * 0.2126 + g * 0.7152 + b * 0.0722;
+        let z = r * 0.0193 + g * 0.1192 + b * 0.9505;
+        let fy = f(y);
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (f(x / 0.95047) - fy),
+            b: 200.0 * (fy - f(z / 1.08883)),
+        }
+    }
+
+    /// The CIEDE2000 color difference between `self` and `other`.
+    fn difference(&self, other: &Self) -> f64 {
+        const POW_25_7: f64 = 6103515625.0;
+
+        /// The hue angle of `(a, b)`, in degrees from 0 to 360.
+        fn hue(b: f64, a: f64) -> f64 {
+            if a == 0.0 && b == 0.0 {
+                return 0.0;
+            }
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+
+        let dl = other.l - self.l;
+        let l_avg = (self.l + other.l) / 2.0;
+        let c_avg = (self.a.hypot(self.b) + other.a.hypot(other.b)) / 2.0;
+        let g = 1.0 - (c_avg.powi(7) / (c_avg.powi(7) + POW_25_7)).sqrt();
+        let a1 = self.a + self.a / 2.0 * g;
+        let a2 = other.a + other.a / 2.0 * g;
+        let c1 = a1.hypot(self.b);
+        let c2 = a2.hypot(other.b);
+        let c_avg = (c1 + c2) / 2.0;
+        let dc = c2 - c1;
+        let h1 = hue(self.b, a1);
+        let h2 = hue(other.b, a2);
+        let dh = match h2 - h1 {
+            d if d.abs() <= 180.0 => d,
+            d if h2 <= h1 => d + 360.0,
+            d => d - 360.0,
+        };
+        let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+        let h_avg = match h1 + h2 {
+            _ if (h1 - h2).abs() <= 180.0 => (h1 + h2) / 2.0,
+            s if s < 360.0 => (s + 360.0) / 2.0,
+            s => (s - 360.0) / 2.0,
+        };
+        let cos = |deg: f64| deg.to_radians().cos();
+        let t = 1.0 - 0.17 * cos(h_avg - 30.0)
+            + 0.24 * cos(2.0 * h_avg)
+            + 0.32 * cos(3.0 * h_avg + 6.0)
+            - 0.20 * cos(4.0 * h_avg - 63.0);
+
+        let l_sq = (l_avg - 50.0).powi(2);
+        let sl = 1.0 + 0.015 * l_sq / (20.0 + l_sq).sqrt();
+        let sc = 1.0 + 0.045 * c_avg;
+        let sh = 1.0 + 0.015 * c_avg * t;
+        let rt = -2.0
+            * (c_avg.powi(7) / (c_avg.powi(7) + POW_25_7)).sqrt()
+            * (60.0 * (-((h_avg - 275.0) / 25.0).powi(2)).exp())
+                .to_radians()
+                .sin();
+
+        let (dl, dc, dh) = (dl / sl, dc / sc, dh / sh);
+        (dl * dl + dc * dc + dh * dh + rt * dc * dh).sqrt()
+    }
+}
@@ -17,13 +17,21 @@
  * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::aliases::Aliases;
+use super::color;
+use super::error::Warning;
+use super::gm;
+use super::keys;
 use super::parse::bounded::BoundedU8;
 use super::parse::config::DeserializeConfig;
-use super::parse::error::IgnoredError;
-use super::parse::primitive;
-use serde::Deserialize;
+use super::parse::{self, primitive};
+use super::warnings::Warnings;
 use serde::de::value::MapAccessDeserializer;
 use serde::de::{self, Deserializer, IntoDeserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_spanned::Spanned;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Display};
 use std::io::{self, Write};
 use std::str::FromStr;
@@ -49,13 +57,22 @@ impl MidiChannel {
     }
 }
 
+impl Serialize for MidiChannel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(self.number())
+    }
+}
+
 impl From<BoundedU8<1, 16>> for MidiChannel {
     fn from(v: BoundedU8<1, 16>) -> Self {
         Self(v.get() - 1)
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
 #[serde(from = "BoundedU8<0, 127>")]
 pub struct MidiValue(u8);
 
@@ -76,6 +93,15 @@ impl MidiValue {
     }
 }
 
+impl Serialize for MidiValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(self.0)
+    }
+}
+
 impl From<BoundedU8<0, 127>> for MidiValue {
     fn from(v: BoundedU8<0, 127>) -> Self {
         Self(v.get())
@@ -83,7 +109,7 @@ impl From<BoundedU8<0, 127>> for MidiValue {
 }
 
 #[derive(Clone, Copy, Debug)]
-struct NoteName(pub MidiValue);
+pub(crate) struct NoteName(pub MidiValue);
 
 impl FromStr for NoteName {
     type Err = NoteNameParseError;
@@ -120,7 +146,7 @@ impl FromStr for NoteName {
 }
 
 #[derive(Clone, Copy, Debug)]
-struct NoteNameParseError;
+pub(crate) struct NoteNameParseError;
 
 impl Display for NoteNameParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -156,7 +182,8 @@ impl<'a> Deserialize<'a> for NoteName {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(transparent)]
 pub struct MidiNote(pub MidiValue);
 
 impl MidiNote {
@@ -171,18 +198,48 @@ impl From<NoteName> for MidiNote {
     }
 }
 
-impl<'a> Deserialize<'a> for MidiNote {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+/// Configuration from the custom mode as a whole, rather than from the
+/// device, that the controls in it are deserialized with.
+#[derive(Clone, Copy, Debug)]
+pub struct ModeCfg<'a> {
+    /// The aliases defined in `[aliases]`.
+    pub aliases: &'a Aliases,
+    /// Where warnings are recorded, if they're wanted.
+    pub warnings: Option<&'a Warnings>,
+}
+
+impl ModeCfg<'_> {
+    /// The configuration of a custom mode with no aliases, whose warnings
+    /// are discarded, as when checking or serializing controls built in code.
+    pub const NONE: ModeCfg<'static> = ModeCfg {
+        aliases: &Aliases::new(),
+        warnings: None,
+    };
+}
+
+/// Uses `config` for names from `[aliases.notes]`.
+impl<'a> DeserializeConfig<'a, Aliases> for MidiNote {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &Aliases,
+    ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
-        struct Visitor;
+        struct Visitor<'a> {
+            aliases: &'a Aliases,
+        }
 
-        impl de::Visitor<'_> for Visitor {
+        impl de::Visitor<'_> for Visitor<'_> {
             type Value = MidiNote;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "0-127 or \"C-1\" to \"G9\"")
+                write!(
+                    f,
+                    "0-127, \"C-1\" to \"G9\", a General MIDI drum like \
+                    \"gm:acoustic-snare\", or an alias from \
+                    `[aliases.notes]`",
+                )
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
@@ -208,81 +265,202 @@ impl<'a> Deserialize<'a> for MidiNote {
             where
                 E: de::Error,
             {
-                match NoteName::deserialize(v.into_deserializer()) {
-                    Ok(name) => Ok(name.into()),
-                    Err(IgnoredError) => {
-                        Err(E::invalid_value(de::Unexpected::Str(v), &self))
-                    }
+                if let Ok(name) = v.parse::<NoteName>() {
+                    return Ok(name.into());
                 }
+                let drum = v.strip_prefix("gm:").and_then(gm::drum);
+                if let Some(note) = drum.and_then(MidiValue::new) {
+                    return Ok(MidiNote(note));
+                }
+                self.aliases.notes.get(v).copied().ok_or_else(|| {
+                    E::invalid_value(de::Unexpected::Str(v), &self)
+                })
             }
         }
 
-        deserializer.deserialize_u8(Visitor)
+        deserializer.deserialize_u8(Visitor {
+            aliases: config,
+        })
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum Velocity {
-    Fixed(MidiValue),
-    Variable,
+/// A kind of MIDI value that may also be given by name.
+#[derive(Clone, Copy)]
+enum NamedValue {
+    Cc,
+    Program,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum VelocityCfg {
-    FixedOnly,
-    VariableOnly,
-    Any,
+impl NamedValue {
+    /// What a name of this kind is called in error messages.
+    fn what(self) -> &'static str {
+        match self {
+            Self::Cc => "controller name",
+            Self::Program => "program name",
+        }
+    }
+
+    fn builtin(self, name: &str) -> Option<u8> {
+        match self {
+            Self::Cc => gm::controller(name),
+            Self::Program => gm::program(name),
+        }
+    }
+
+    fn builtin_names(self) -> Box<dyn Iterator<Item = &'static str>> {
+        match self {
+            Self::Cc => Box::new(gm::controllers()),
+            Self::Program => Box::new(gm::programs()),
+        }
+    }
+
+    fn aliases(self, aliases: &Aliases) -> &BTreeMap<String, MidiValue> {
+        match self {
+            Self::Cc => &aliases.cc,
+            Self::Program => &aliases.programs,
+        }
+    }
+
+    fn deserialize<'a, D>(
+        self,
+        deserializer: D,
+        aliases: &Aliases,
+    ) -> Result<MidiValue, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_u8(NamedValueVisitor {
+            kind: self,
+            aliases,
+        })
+    }
 }
 
-impl VelocityCfg {
-    pub const fn fixed_allowed(self) -> bool {
-        matches!(self, Self::FixedOnly | Self::Any)
+struct NamedValueVisitor<'a> {
+    kind: NamedValue,
+    aliases: &'a Aliases,
+}
+
+impl de::Visitor<'_> for NamedValueVisitor<'_> {
+    type Value = MidiValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            NamedValue::Cc => write!(
+                f,
+                "0-127, a controller name like \"modulation\", or an alias \
+                from `[aliases.cc]`",
+            ),
+            NamedValue::Program => write!(
+                f,
+                "0-127, a General MIDI instrument like \"electric-piano-1\", \
+                or an alias from `[aliases.programs]`",
+            ),
+        }
     }
 
-    pub const fn variable_allowed(self) -> bool {
-        matches!(self, Self::VariableOnly | Self::Any)
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        MidiValue::deserialize(v.into_deserializer())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v.try_into() {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(E::invalid_type(de::Unexpected::Signed(v), &self)),
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let kind = self.kind;
+        if let Some(value) = kind.builtin(v).and_then(MidiValue::new) {
+            return Ok(value);
+        }
+        let aliases = kind.aliases(self.aliases);
+        if let Some(value) = aliases.get(v) {
+            return Ok(*value);
+        }
+        let builtin = kind.builtin_names().map(|n| -> &str { n });
+        let names = aliases.keys().map(String::as_str).chain(builtin);
+        let matches = parse::near_matches(v, names);
+        if matches.is_empty() {
+            Err(E::invalid_value(de::Unexpected::Str(v), &self))
+        } else {
+            Err(E::custom(parse::did_you_mean(kind.what(), v, &matches)))
+        }
     }
 }
 
-impl<'a> DeserializeConfig<'a, VelocityCfg> for Velocity {
+/// A CC number, which may also be given as the name of a standard
+/// controller (like `modulation`) or an alias from `[aliases.cc]`.
+#[derive(Clone, Copy, Debug)]
+pub struct CcNumber(pub MidiValue);
+
+/// Uses `config` for names from `[aliases.cc]`.
+impl<'a> DeserializeConfig<'a, Aliases> for CcNumber {
     fn deserialize<D>(
         deserializer: D,
-        config: &VelocityCfg,
+        config: &Aliases,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
-        struct Visitor<'a> {
-            cfg: &'a VelocityCfg,
+        NamedValue::Cc.deserialize(deserializer, config).map(Self)
+    }
+}
+
+/// A color number from the palette, which may also be given as the name of a
+/// color (like `red`) or as a hex color (like `#ff8800`), which is replaced
+/// with the closest color in the palette.
+#[derive(Clone, Copy, Debug)]
+pub struct Color(pub MidiValue);
+
+/// Records a warning in `config` for a hex color far from any in the palette.
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for Color {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        /// The color difference above which a hex color is far enough from
+        /// the closest color in the palette to warn about. Saturated colors
+        /// like `#ff0000` are around 15 from their closest match.
+        const FAR: f64 = 20.0;
+
+        enum Value {
+            Number(MidiValue),
+            Hex(String, [u8; 3]),
         }
 
-        impl de::Visitor<'_> for Visitor<'_> {
-            type Value = Velocity;
+        struct Visitor;
+
+        impl<'a> de::Visitor<'a> for Visitor {
+            type Value = Value;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                match self.cfg {
-                    VelocityCfg::FixedOnly => {
-                        write!(f, "an integer between 0 and 127")
-                    }
-                    VelocityCfg::VariableOnly => {
-                        write!(f, "\"variable\"")
-                    }
-                    VelocityCfg::Any => {
-                        write!(f, "0-127 or \"variable\"")
-                    }
-                }
+                write!(
+                    f,
+                    "0-127, a color name like \"red\", or a hex color like \
+                    \"#ff8800\"",
+                )
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
-                if self.cfg.fixed_allowed() {
-                    MidiValue::deserialize(v.into_deserializer())
-                        .map(Velocity::Fixed)
-                } else {
-                    Err(E::invalid_type(de::Unexpected::Unsigned(v), &self))
-                }
+                MidiValue::deserialize(v.into_deserializer())
+                    .map(Value::Number)
             }
 
             fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
@@ -301,17 +479,200 @@ impl<'a> DeserializeConfig<'a, VelocityCfg> for Velocity {
             where
                 E: de::Error,
             {
-                if !self.cfg.variable_allowed() {
-                    Err(E::invalid_type(de::Unexpected::Str(v), &self))
-                } else if v == "variable" {
-                    Ok(Velocity::Variable)
-                } else {
+                if let Some(color) = color::named(v).and_then(MidiValue::new) {
+                    return Ok(Value::Number(color));
+                }
+                if v.starts_with('#') {
+                    return match color::parse_hex(v) {
+                        Some(rgb) => Ok(Value::Hex(v.into(), rgb)),
+                        None => Err(E::invalid_value(
+                            de::Unexpected::Str(v),
+                            &self,
+                        )),
+                    };
+                }
+                let matches = parse::near_matches(v, color::names());
+                if matches.is_empty() {
                     Err(E::invalid_value(de::Unexpected::Str(v), &self))
+                } else {
+                    Err(E::custom(parse::did_you_mean("color", v, &matches)))
                 }
             }
         }
 
-        deserializer.deserialize_any(Visitor {
+        impl<'a> Deserialize<'a> for Value {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'a>,
+            {
+                deserializer.deserialize_any(Visitor)
+            }
+        }
+
+        let value = Spanned::<Value>::deserialize(deserializer)?;
+        let span = value.span();
+        let (hex, rgb) = match value.into_inner() {
+            Value::Number(color) => return Ok(Self(color)),
+            Value::Hex(hex, rgb) => (hex, rgb),
+        };
+        let (color, difference) = color::nearest(rgb);
+        if let Some(warnings) = config.warnings.filter(|_| difference > FAR) {
+            warnings.warn(Warning::new(
+                format_args!(
+                    "no color in the palette is close to `{hex}`; using the \
+                    closest, {} (`{}`)",
+                    color.value(),
+                    color::hex(color),
+                ),
+                Some(span),
+            ));
+        }
+        Ok(Self(color))
+    }
+}
+
+/// A program number, which may also be given as the name of a General MIDI
+/// instrument (like `electric-piano-1`) or an alias from
+/// `[aliases.programs]`.
+#[derive(Clone, Copy, Debug)]
+pub struct ProgramNumber(pub MidiValue);
+
+/// Uses `config` for names from `[aliases.programs]`.
+impl<'a> DeserializeConfig<'a, Aliases> for ProgramNumber {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &Aliases,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        NamedValue::Program.deserialize(deserializer, config).map(Self)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Velocity {
+    Fixed(MidiValue),
+    Variable,
+}
+
+impl Velocity {
+    pub fn is_variable(&self) -> bool {
+        matches!(self, Self::Variable)
+    }
+
+    /// Checks that this velocity is allowed by `config`, producing the same
+    /// error as deserialization.
+    pub fn check<E: de::Error>(&self, config: &VelocityCfg) -> Result<(), E> {
+        match self {
+            Self::Fixed(v) if !config.fixed_allowed() => Err(E::invalid_type(
+                de::Unexpected::Unsigned(v.value().into()),
+                &VelocityVisitor {
+                    cfg: config,
+                },
+            )),
+            Self::Variable if !config.variable_allowed() => {
+                Err(E::missing_field("velocity"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Serialize for Velocity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Fixed(v) => v.serialize(serializer),
+            Self::Variable => serializer.serialize_str("variable"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VelocityCfg {
+    FixedOnly,
+    VariableOnly,
+    Any,
+}
+
+impl VelocityCfg {
+    pub const fn fixed_allowed(self) -> bool {
+        matches!(self, Self::FixedOnly | Self::Any)
+    }
+
+    pub const fn variable_allowed(self) -> bool {
+        matches!(self, Self::VariableOnly | Self::Any)
+    }
+}
+
+struct VelocityVisitor<'a> {
+    cfg: &'a VelocityCfg,
+}
+
+impl de::Visitor<'_> for VelocityVisitor<'_> {
+    type Value = Velocity;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cfg {
+            VelocityCfg::FixedOnly => {
+                write!(f, "an integer between 0 and 127")
+            }
+            VelocityCfg::VariableOnly => {
+                write!(f, "\"variable\"")
+            }
+            VelocityCfg::Any => {
+                write!(f, "0-127 or \"variable\"")
+            }
+        }
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if self.cfg.fixed_allowed() {
+            MidiValue::deserialize(v.into_deserializer()).map(Velocity::Fixed)
+        } else {
+            Err(E::invalid_type(de::Unexpected::Unsigned(v), &self))
+        }
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v.try_into() {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(E::invalid_type(de::Unexpected::Signed(v), &self)),
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if !self.cfg.variable_allowed() {
+            Err(E::invalid_type(de::Unexpected::Str(v), &self))
+        } else if v == "variable" {
+            Ok(Velocity::Variable)
+        } else {
+            Err(E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, VelocityCfg> for Velocity {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &VelocityCfg,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_any(VelocityVisitor {
             cfg: config,
         })
     }
@@ -323,6 +684,24 @@ pub enum Channel {
     Global,
 }
 
+impl Channel {
+    pub fn is_global(&self) -> bool {
+        matches!(self, Self::Global)
+    }
+}
+
+impl Serialize for Channel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Fixed(c) => c.serialize(serializer),
+            Self::Global => serializer.serialize_str("global"),
+        }
+    }
+}
+
 impl<'a> Deserialize<'a> for Channel {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -393,6 +772,10 @@ impl Name {
         &self.bytes[..self.len.into()]
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn as_str(&self) -> &str {
         debug_assert!(self.bytes.is_ascii());
         // SAFETY: Contents are always ASCII.
@@ -447,6 +830,15 @@ impl<'a> Deserialize<'a> for Name {
     }
 }
 
+impl Serialize for Name {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 impl FromStr for Name {
     type Err = NameParseError;
 
@@ -503,6 +895,18 @@ pub struct Keypress {
     pub alt: bool,
 }
 
+impl Keypress {
+    /// Creates a keypress without modifiers.
+    pub const fn new(code: u8) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+}
+
 impl<'a> Deserialize<'a> for Keypress {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -539,7 +943,11 @@ impl<'a> Deserialize<'a> for Keypress {
             type Value = Keypress;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "0-255 or map with keycode and modifiers")
+                write!(
+                    f,
+                    "0-255, a key name with modifiers like \"ctrl+shift+F5\", \
+                    or map with keycode and modifiers",
+                )
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
@@ -566,6 +974,45 @@ impl<'a> Deserialize<'a> for Keypress {
                 }
             }
 
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                const MODIFIERS: [&str; 3] = ["ctrl", "shift", "alt"];
+                let (modifiers, name) = match v.rsplit_once('+') {
+                    Some((modifiers, name)) => (Some(modifiers), name),
+                    None => (None, v),
+                };
+                let mut key = Keypress::new(match keys::key(name) {
+                    Some(code) => code,
+                    None => return Err(unknown_key(name, &self)),
+                });
+                for modifier in
+                    modifiers.into_iter().flat_map(|m| m.split('+'))
+                {
+                    let flag = match &*modifier.to_ascii_lowercase() {
+                        "ctrl" | "control" => &mut key.ctrl,
+                        "shift" => &mut key.shift,
+                        "alt" => &mut key.alt,
+                        _ => {
+                            return Err(E::custom(format_args!(
+                                "unknown modifier `{}`; expected {}",
+                                modifier.escape_default(),
+                                parse::one_of(MODIFIERS),
+                            )));
+                        }
+                    };
+                    if *flag {
+                        return Err(E::custom(format_args!(
+                            "duplicate modifier `{}`",
+                            modifier.escape_default(),
+                        )));
+                    }
+                    *flag = true;
+                }
+                Ok(key)
+            }
+
             fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
             where
                 A: de::MapAccess<'a>,
@@ -575,6 +1022,62 @@ impl<'a> Deserialize<'a> for Keypress {
             }
         }
 
+        /// Rejects the key `name`, suggesting similar key names if there
+        /// are any.
+        fn unknown_key<E: de::Error>(name: &str, visitor: &Visitor) -> E {
+            if name.is_empty() {
+                return E::invalid_value(de::Unexpected::Str(name), visitor);
+            }
+            let lower: Vec<_> =
+                keys::keys().map(str::to_ascii_lowercase).collect();
+            let matches: Vec<_> = parse::near_matches(
+                &name.to_ascii_lowercase(),
+                lower.iter().map(String::as_str),
+            )
+            .into_iter()
+            .filter_map(|m| keys::keys().find(|k| k.eq_ignore_ascii_case(m)))
+            .collect();
+            if matches.is_empty() {
+                E::invalid_value(de::Unexpected::Str(name), visitor)
+            } else {
+                E::custom(parse::did_you_mean("key name", name, &matches))
+            }
+        }
+
         deserializer.deserialize_map(Visitor)
     }
 }
+
+impl Serialize for Keypress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn is_false(v: &bool) -> bool {
+            !v
+        }
+
+        #[derive(Serialize)]
+        struct Fields {
+            keycode: u8,
+            #[serde(skip_serializing_if = "is_false")]
+            ctrl: bool,
+            #[serde(skip_serializing_if = "is_false")]
+            shift: bool,
+            #[serde(skip_serializing_if = "is_false")]
+            alt: bool,
+        }
+
+        if self.ctrl || self.shift || self.alt {
+            Fields {
+                keycode: self.code,
+                ctrl: self.ctrl,
+                shift: self.shift,
+                alt: self.alt,
+            }
+            .serialize(serializer)
+        } else {
+            serializer.serialize_u8(self.code)
+        }
+    }
+}
@@ -19,7 +19,9 @@
 
 use super::CompileCfg;
 use super::{pad, pad_fader, pot};
-use crate::common::{Channel, Keypress, MidiNote, MidiValue, Velocity};
+use crate::common::Velocity;
+use crate::common::{Channel, Keypress, MidiChannel, MidiNote, MidiValue};
+use crate::decompile::{self, Reader};
 use std::io::{self, Write};
 
 #[derive(Clone, Copy, Debug)]
@@ -82,6 +84,47 @@ impl Opt {
             cfg: config,
         }
     }
+
+    pub fn channel(&self) -> Channel {
+        if self.b2 & 0x40 != 0 {
+            return Channel::Global;
+        }
+        match MidiChannel::new((self.b1 & 0x0f) + 1) {
+            Some(c) => Channel::Fixed(c),
+            None => unreachable!(),
+        }
+    }
+
+    pub fn key(&self, code: u8) -> Keypress {
+        Keypress {
+            code,
+            ctrl: self.b1 & 0x01 != 0,
+            shift: self.b1 & 0x02 != 0,
+            alt: self.b1 & 0x04 != 0,
+        }
+    }
+
+    pub fn behavior(&self) -> pad::Behavior {
+        match self.b1 & 0x20 {
+            0 => pad::Behavior::Momentary,
+            _ => pad::Behavior::Toggle,
+        }
+    }
+
+    pub fn pickup(&self) -> pot::Pickup {
+        match self.b1 & 0x30 {
+            0x10 => pot::Pickup::Yes,
+            0x20 => pot::Pickup::No,
+            _ => pot::Pickup::Global,
+        }
+    }
+
+    pub fn pad_fader(&self) -> pad_fader::Mode {
+        match self.b2 & 0x0f {
+            0x09 => pad_fader::Mode::Bipolar,
+            _ => pad_fader::Mode::Unipolar,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -224,6 +267,72 @@ impl Definition {
             ]),
         }
     }
+
+    /// Reads a definition for the control at `address`.
+    pub fn read(
+        reader: &mut Reader<'_>,
+        address: u8,
+    ) -> Result<Self, decompile::Error> {
+        let len = reader.byte()?.wrapping_sub(0x45);
+        reader.expect(&[address])?;
+        let id = reader.byte()?;
+        let color = reader.value()?;
+        reader.expect(&[0x00])?;
+        let [b1, b2] = reader.array()?;
+        let opt = Opt {
+            b1,
+            b2,
+        };
+        let payload = match (id, len) {
+            (0x01, 3) => {
+                reader.expect(&[0x00])?;
+                let velocity = reader.value()?;
+                Payload::Note {
+                    velocity: match b2 & 0x04 {
+                        0 => Velocity::Variable,
+                        _ => Velocity::Fixed(velocity),
+                    },
+                    pitch: MidiNote(reader.value()?),
+                }
+            }
+            (0x02, 4) => {
+                reader.expect(&[0x00])?;
+                let number = reader.value()?;
+                let max = reader.value()?;
+                Payload::Cc {
+                    number,
+                    min: reader.value()?,
+                    max,
+                }
+            }
+            (0x02, 3) => {
+                reader.expect(&[0x00])?;
+                let number = reader.value()?;
+                reader.expect(&[0x7f])?;
+                Payload::CcFull(number)
+            }
+            (0x03, 4) => {
+                reader.expect(&[0x00, 0x00])?;
+                let number = reader.value()?;
+                reader.expect(&[number.value()])?;
+                Payload::Prog(number)
+            }
+            (0x0c, 2) => {
+                let [high, low] = reader.array()?;
+                if high > 1 || low > 0x7f {
+                    return Err(decompile::Error::Control(address));
+                }
+                Payload::Key((high << 7) | low)
+            }
+            _ => return Err(decompile::Error::Control(address)),
+        };
+        Ok(Self {
+            address,
+            color,
+            opt,
+            payload,
+        })
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -238,4 +347,17 @@ impl Empty {
     {
         writer.write_all(&[0x40, self.address])
     }
+
+    /// Reads an empty definition for the control at `address`, if one is
+    /// present. Returns whether the definition was found.
+    pub fn read(
+        reader: &mut Reader<'_>,
+        address: u8,
+    ) -> Result<bool, decompile::Error> {
+        if reader.peek() != Some(0x40) {
+            return Ok(false);
+        }
+        reader.expect(&[0x40, address])?;
+        Ok(true)
+    }
 }
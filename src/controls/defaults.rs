@@ -0,0 +1,242 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The `[defaults]` table of a map, which supplies the fields that controls
+//! don't specify.
+
+use super::pad::{PadCfg, PadDefaults};
+use super::pad_fader::{FaderCfg, FaderDefaults};
+use super::pot::{PotCfg, PotDefaults};
+use crate::parse;
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
+use serde::de::{self, Deserializer};
+use std::fmt::{self, Display};
+
+/// Defaults for each kind of control in a map.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Defaults {
+    pub pad: PadDefaults,
+    pub pot: PotDefaults,
+    pub fader: FaderDefaults,
+}
+
+/// The kinds of controls whose defaults may be given, and the keys used for
+/// them.
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultsCfg<'a> {
+    pad: Option<(&'static str, &'a PadCfg<'a>)>,
+    pot: Option<(&'static str, &'a PotCfg<'a>)>,
+    fader: Option<&'a FaderCfg<'a>>,
+}
+
+impl<'a> DefaultsCfg<'a> {
+    pub const fn new() -> Self {
+        Self {
+            pad: None,
+            pot: None,
+            fader: None,
+        }
+    }
+
+    /// Allows defaults for pads (stored in [`Defaults::pad`]) under `key`.
+    pub const fn pad(
+        mut self,
+        key: &'static str,
+        cfg: &'a PadCfg<'a>,
+    ) -> Self {
+        self.pad = Some((key, cfg));
+        self
+    }
+
+    /// Allows defaults for pots (stored in [`Defaults::pot`]) under `key`.
+    pub const fn pot(
+        mut self,
+        key: &'static str,
+        cfg: &'a PotCfg<'a>,
+    ) -> Self {
+        self.pot = Some((key, cfg));
+        self
+    }
+
+    /// Allows defaults for Launchpad faders under the key `fader`.
+    pub const fn fader(mut self, cfg: &'a FaderCfg<'a>) -> Self {
+        self.fader = Some(cfg);
+        self
+    }
+
+    fn keys(&self) -> impl Clone + Iterator<Item = &'static str> {
+        [
+            self.pad.map(|(key, _)| key),
+            self.pot.map(|(key, _)| key),
+            self.fader.map(|_| "fader"),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl Default for DefaultsCfg<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Field {
+    Pad,
+    Pot,
+    Fader,
+}
+
+struct FieldVisitor<'a> {
+    cfg: &'a DefaultsCfg<'a>,
+}
+
+impl de::Visitor<'_> for FieldVisitor<'_> {
+    type Value = Field;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        #[derive(Clone, Copy)]
+        struct UnknownField<'a> {
+            name: &'a str,
+            cfg: &'a DefaultsCfg<'a>,
+        }
+
+        impl Display for UnknownField<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "unknown key `{}`; expected {}",
+                    self.name.escape_default(),
+                    parse::one_of(self.cfg.keys()),
+                )
+            }
+        }
+
+        let cfg = self.cfg;
+        if cfg.pad.is_some_and(|(key, _)| key == v) {
+            Ok(Field::Pad)
+        } else if cfg.pot.is_some_and(|(key, _)| key == v) {
+            Ok(Field::Pot)
+        } else if cfg.fader.is_some() && v == "fader" {
+            Ok(Field::Fader)
+        } else {
+            Err(E::custom(UnknownField {
+                name: v,
+                cfg,
+            }))
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, DefaultsCfg<'_>> for Field {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &DefaultsCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(FieldVisitor {
+            cfg: config,
+        })
+    }
+}
+
+impl<'a> DeserializeConfig<'a, DefaultsCfg<'_>> for Defaults {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &DefaultsCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        struct Visitor<'a> {
+            cfg: &'a DefaultsCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = Defaults;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "defaults (table)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut pad = None;
+                let mut pot = None;
+                let mut fader = None;
+                while let Some(field) =
+                    map.next_key_seed(ConfigSeed::new(self.cfg))?
+                {
+                    let cfg = self.cfg;
+                    match (field, cfg.pad, cfg.pot, cfg.fader) {
+                        (Field::Pad, Some((key, cfg)), _, _) => {
+                            parse::check_dup(&pad, key)?;
+                            let seed = ConfigSeed::new(cfg);
+                            pad = Some(map.next_value_seed(seed)?);
+                        }
+                        (Field::Pot, _, Some((key, cfg)), _) => {
+                            parse::check_dup(&pot, key)?;
+                            let seed = ConfigSeed::new(cfg);
+                            pot = Some(map.next_value_seed(seed)?);
+                        }
+                        (Field::Fader, _, _, Some(cfg)) => {
+                            parse::check_dup(&fader, "fader")?;
+                            let seed = ConfigSeed::new(cfg);
+                            fader = Some(map.next_value_seed(seed)?);
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                Ok(Defaults {
+                    pad: pad.unwrap_or_default(),
+                    pot: pot.unwrap_or_default(),
+                    fader: fader.unwrap_or_default(),
+                })
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
+    }
+}
+
+/// Returns an error if `controls`, the controls under `key` that a map's
+/// `[defaults]` would apply to, have already been deserialized.
+pub fn check_order<T, E>(controls: &Option<T>, key: &str) -> Result<(), E>
+where
+    E: de::Error,
+{
+    if controls.is_some() {
+        Err(E::custom(format_args!("`defaults` must come before `{key}`")))
+    } else {
+        Ok(())
+    }
+}
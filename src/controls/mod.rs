@@ -17,9 +17,11 @@
  * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::decompile::{self, Reader};
 use std::io::{self, Write};
 
 mod def;
+pub mod defaults;
 pub mod pad;
 pub mod pad_fader;
 pub mod pedal;
@@ -29,17 +31,25 @@ pub use pad::Pad;
 pub use pedal::Pedal;
 pub use pot::Pot;
 
-pub trait Control {
+pub trait Control: Sized {
     fn compile<W: Write>(
         &self,
         address: u8,
         writer: &mut W,
         config: &CompileCfg,
     ) -> io::Result<()>;
+
+    /// Reads a control previously written by [`Self::compile`].
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        config: &CompileCfg,
+    ) -> Result<Self, decompile::Error>;
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub enum Optional<T> {
+    #[default]
     None,
     Some(T),
 }
@@ -59,12 +69,6 @@ impl<'a, T> From<&'a Optional<T>> for Option<&'a T> {
     }
 }
 
-impl<T> Default for Optional<T> {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl<T: Control> Control for Optional<T> {
     fn compile<W: Write>(
         &self,
@@ -80,6 +84,18 @@ impl<T: Control> Control for Optional<T> {
             Self::Some(c) => c.compile(address, writer, config),
         }
     }
+
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        config: &CompileCfg,
+    ) -> Result<Self, decompile::Error> {
+        if def::Empty::read(reader, address)? {
+            Ok(Self::None)
+        } else {
+            T::decompile(address, reader, config).map(Self::Some)
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -19,22 +19,32 @@
 
 use super::def;
 use super::{CompileCfg, Control, Optional};
-use crate::common::{Channel, Keypress, MidiNote, MidiValue};
+use crate::common::{CcNumber, Channel, Color, Keypress, MidiNote};
+use crate::common::{MidiValue, ModeCfg, ProgramNumber};
 use crate::common::{Velocity, VelocityCfg};
+use crate::decompile::{self, Reader};
 use crate::parse;
 use crate::parse::config::{ConfigSeed, DeserializeConfig};
-use serde::Deserialize;
-use serde::de::value::MapAccessDeserializer;
+use crate::ser::SerializeConfig;
 use serde::de::{self, Deserializer, IntoDeserializer};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Display};
 use std::io::{self, Write};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Behavior {
     Momentary,
     Toggle,
 }
 
+impl Behavior {
+    fn is_momentary(&self) -> bool {
+        *self == Self::Momentary
+    }
+}
+
 impl<'a> Deserialize<'a> for Behavior {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -73,23 +83,86 @@ pub struct Note {
     pub behavior: Behavior,
 }
 
+impl Serialize for Note {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields {
+            pitch: MidiNote,
+            #[serde(skip_serializing_if = "Channel::is_global")]
+            channel: Channel,
+            #[serde(skip_serializing_if = "Velocity::is_variable")]
+            velocity: Velocity,
+            #[serde(skip_serializing_if = "Behavior::is_momentary")]
+            behavior: Behavior,
+        }
+
+        match self {
+            Note {
+                pitch,
+                channel: Channel::Global,
+                velocity: Velocity::Variable,
+                behavior: Behavior::Momentary,
+            } => pitch.serialize(serializer),
+            _ => Fields {
+                pitch: self.pitch,
+                channel: self.channel,
+                velocity: self.velocity,
+                behavior: self.behavior,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct NoteCfg {
+pub struct NoteCfg<'a> {
     velocity: VelocityCfg,
+    defaults: PadDefaults,
+    mode: ModeCfg<'a>,
 }
 
-impl NoteCfg {
-    pub const fn new(velocity: VelocityCfg) -> Self {
+impl<'a> NoteCfg<'a> {
+    pub const fn new(velocity: VelocityCfg, mode: ModeCfg<'a>) -> Self {
         Self {
             velocity,
+            defaults: PadDefaults::new(),
+            mode,
         }
     }
+
+    pub const fn defaults(mut self, defaults: PadDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// The velocity used when a note doesn't specify one, if any.
+    fn default_velocity(&self) -> Option<Velocity> {
+        self.defaults.velocity.or_else(|| {
+            self.velocity.variable_allowed().then_some(Velocity::Variable)
+        })
+    }
 }
 
-impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
+impl Note {
+    /// Creates a momentary note with variable velocity on the global
+    /// channel.
+    pub const fn new(pitch: MidiNote) -> Self {
+        Self {
+            pitch,
+            channel: Channel::Global,
+            velocity: Velocity::Variable,
+            behavior: Behavior::Momentary,
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, NoteCfg<'_>> for Note {
     fn deserialize<D>(
         deserializer: D,
-        config: &NoteCfg,
+        config: &NoteCfg<'_>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -104,7 +177,7 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
         #[derive(Clone, Copy)]
         struct UnknownField<'a> {
             name: &'a str,
-            cfg: &'a NoteCfg,
+            cfg: &'a NoteCfg<'a>,
         }
 
         impl Display for UnknownField<'_> {
@@ -125,7 +198,7 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
         }
 
         struct FieldVisitor<'a> {
-            cfg: &'a NoteCfg,
+            cfg: &'a NoteCfg<'a>,
         }
 
         impl de::Visitor<'_> for FieldVisitor<'_> {
@@ -152,10 +225,10 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
             }
         }
 
-        impl<'a> DeserializeConfig<'a, NoteCfg> for Field {
+        impl<'a> DeserializeConfig<'a, NoteCfg<'_>> for Field {
             fn deserialize<D>(
                 deserializer: D,
-                config: &NoteCfg,
+                config: &NoteCfg<'_>,
             ) -> Result<Self, D::Error>
             where
                 D: Deserializer<'a>,
@@ -167,7 +240,7 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
         }
 
         struct Visitor<'a> {
-            cfg: &'a NoteCfg,
+            cfg: &'a NoteCfg<'a>,
         }
 
         impl Visitor<'_> {
@@ -177,12 +250,18 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
                 E: de::Error,
                 F: FnOnce(T) -> de::Unexpected<'a>,
             {
-                if self.cfg.velocity.variable_allowed() {
+                let defaults = &self.cfg.defaults;
+                if let Some(velocity) = self.cfg.default_velocity() {
                     Ok(Note {
-                        pitch: MidiNote::deserialize(v.into_deserializer())?,
-                        channel: Channel::Global,
-                        velocity: Velocity::Variable,
-                        behavior: Behavior::Momentary,
+                        pitch: MidiNote::deserialize(
+                            v.into_deserializer(),
+                            self.cfg.mode.aliases,
+                        )?,
+                        channel: defaults.channel.unwrap_or(Channel::Global),
+                        velocity,
+                        behavior: defaults
+                            .behavior
+                            .unwrap_or(Behavior::Momentary),
                     })
                 } else {
                     Err(E::invalid_type(unexp(v), &self))
@@ -197,7 +276,7 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
                 write!(
                     f,
                     "note definition ({})",
-                    if self.cfg.velocity.variable_allowed() {
+                    if self.cfg.default_velocity().is_some() {
                         "integer, string, or table"
                     } else {
                         "table"
@@ -245,7 +324,8 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
                     match field {
                         Field::Pitch => {
                             parse::check_dup(&pitch, "pitch")?;
-                            pitch = Some(map.next_value()?);
+                            let seed = ConfigSeed::new(self.cfg.mode.aliases);
+                            pitch = Some(map.next_value_seed(seed)?);
                         }
                         Field::Channel => {
                             parse::check_dup(&channel, "channel")?;
@@ -263,18 +343,19 @@ impl<'a> DeserializeConfig<'a, NoteCfg> for Note {
                     }
                 }
                 let missing = de::Error::missing_field;
-                let velocity = if let Some(v) = velocity {
-                    v
-                } else if self.cfg.velocity.variable_allowed() {
-                    Velocity::Variable
-                } else {
-                    return Err(missing("velocity"));
-                };
+                let defaults = &self.cfg.defaults;
+                let velocity = velocity
+                    .or_else(|| self.cfg.default_velocity())
+                    .ok_or_else(|| missing("velocity"))?;
                 Ok(Note {
                     pitch: pitch.ok_or_else(|| missing("pitch"))?,
-                    channel: channel.unwrap_or(Channel::Global),
+                    channel: channel
+                        .or(defaults.channel)
+                        .unwrap_or(Channel::Global),
                     velocity,
-                    behavior: behavior.unwrap_or(Behavior::Momentary),
+                    behavior: behavior
+                        .or(defaults.behavior)
+                        .unwrap_or(Behavior::Momentary),
                 })
             }
         }
@@ -294,74 +375,79 @@ pub struct Cc {
     pub behavior: Behavior,
 }
 
+impl Cc {
+    /// Creates a momentary CC that is 127 when on and 0 when off, on the
+    /// global channel.
+    pub const fn new(number: MidiValue) -> Self {
+        Self {
+            number,
+            channel: Channel::Global,
+            off: MidiValue::MIN,
+            on: MidiValue::MAX,
+            behavior: Behavior::Momentary,
+        }
+    }
+}
+
 impl<'a> Deserialize<'a> for Cc {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
-        fn channel_default() -> Channel {
-            Channel::Global
-        }
-
-        fn off_default() -> MidiValue {
-            MidiValue::MIN
-        }
-
-        fn on_default() -> MidiValue {
-            MidiValue::MAX
-        }
+        let cfg = PadCfg::new(VelocityCfg::Any, ModeCfg::NONE);
+        DeserializeConfig::deserialize(deserializer, &cfg)
+    }
+}
 
-        fn behavior_default() -> Behavior {
-            Behavior::Momentary
+/// Uses the defaults in `config` for the fields that aren't specified.
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Cc {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &PadCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Number,
+            Channel,
+            Off,
+            On,
+            Behavior,
         }
 
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields)]
-        #[serde(expecting = "cc definition (table)")]
-        struct Fields {
-            number: MidiValue,
-            #[serde(default = "channel_default")]
-            channel: Channel,
-            #[serde(default = "off_default")]
-            off: MidiValue,
-            #[serde(default = "on_default")]
-            on: MidiValue,
-            #[serde(default = "behavior_default")]
-            behavior: Behavior,
+        struct Visitor<'a> {
+            cfg: &'a PadCfg<'a>,
         }
 
-        impl From<Fields> for Cc {
-            fn from(f: Fields) -> Self {
-                Self {
-                    number: f.number,
-                    channel: f.channel,
-                    off: f.off,
-                    on: f.on,
-                    behavior: f.behavior,
+        impl Visitor<'_> {
+            fn cc(&self, number: MidiValue) -> Cc {
+                let defaults = &self.cfg.defaults;
+                Cc {
+                    number,
+                    channel: defaults.channel.unwrap_or(Channel::Global),
+                    off: MidiValue::MIN,
+                    on: MidiValue::MAX,
+                    behavior: defaults.behavior.unwrap_or(Behavior::Momentary),
                 }
             }
         }
 
-        struct Visitor;
-
-        impl<'a> de::Visitor<'a> for Visitor {
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
             type Value = Cc;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "cc definition (integer or table)")
+                write!(f, "cc definition (integer, name, or table)")
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
-                Ok(Cc {
-                    number: MidiValue::deserialize(v.into_deserializer())?,
-                    channel: channel_default(),
-                    off: off_default(),
-                    on: on_default(),
-                    behavior: behavior_default(),
-                })
+                Ok(self.cc(MidiValue::deserialize(v.into_deserializer())?))
             }
 
             fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
@@ -376,16 +462,66 @@ impl<'a> Deserialize<'a> for Cc {
                 }
             }
 
-            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let aliases = self.cfg.mode.aliases;
+                let number =
+                    CcNumber::deserialize(v.into_deserializer(), aliases)?;
+                Ok(self.cc(number.0))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: de::MapAccess<'a>,
             {
-                Fields::deserialize(MapAccessDeserializer::new(map))
-                    .map(Into::into)
+                let mut number = None;
+                let mut channel = None;
+                let mut off = None;
+                let mut on = None;
+                let mut behavior = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Number => {
+                            parse::check_dup(&number, "number")?;
+                            let seed = ConfigSeed::new(self.cfg.mode.aliases);
+                            number = Some(map.next_value_seed(seed)?);
+                        }
+                        Field::Channel => {
+                            parse::check_dup(&channel, "channel")?;
+                            channel = Some(map.next_value()?);
+                        }
+                        Field::Off => {
+                            parse::check_dup(&off, "off")?;
+                            off = Some(map.next_value()?);
+                        }
+                        Field::On => {
+                            parse::check_dup(&on, "on")?;
+                            on = Some(map.next_value()?);
+                        }
+                        Field::Behavior => {
+                            parse::check_dup(&behavior, "behavior")?;
+                            behavior = Some(map.next_value()?);
+                        }
+                    }
+                }
+                let number: CcNumber = number
+                    .ok_or_else(|| de::Error::missing_field("number"))?;
+                let cc = self.cc(number.0);
+                Ok(Cc {
+                    channel: channel.unwrap_or(cc.channel),
+                    off: off.unwrap_or(cc.off),
+                    on: on.unwrap_or(cc.on),
+                    behavior: behavior.unwrap_or(cc.behavior),
+                    ..cc
+                })
             }
         }
 
-        deserializer.deserialize_map(Visitor)
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
     }
 }
 
@@ -395,50 +531,124 @@ pub struct Prog {
     pub channel: Channel,
 }
 
-impl<'a> Deserialize<'a> for Prog {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+impl Serialize for Cc {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        D: Deserializer<'a>,
+        S: Serializer,
     {
-        fn channel_default() -> Channel {
-            Channel::Global
+        fn is_min(v: &MidiValue) -> bool {
+            *v == MidiValue::MIN
         }
 
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields)]
-        #[serde(expecting = "program change definition (table)")]
+        fn is_max(v: &MidiValue) -> bool {
+            *v == MidiValue::MAX
+        }
+
+        #[derive(Serialize)]
         struct Fields {
             number: MidiValue,
-            #[serde(default = "channel_default")]
+            #[serde(skip_serializing_if = "Channel::is_global")]
             channel: Channel,
+            #[serde(skip_serializing_if = "is_min")]
+            off: MidiValue,
+            #[serde(skip_serializing_if = "is_max")]
+            on: MidiValue,
+            #[serde(skip_serializing_if = "Behavior::is_momentary")]
+            behavior: Behavior,
         }
 
-        impl From<Fields> for Prog {
-            fn from(f: Fields) -> Self {
-                Self {
-                    number: f.number,
-                    channel: f.channel,
-                }
+        match self {
+            Cc {
+                number,
+                channel: Channel::Global,
+                off: MidiValue::MIN,
+                on: MidiValue::MAX,
+                behavior: Behavior::Momentary,
+            } => number.serialize(serializer),
+            _ => Fields {
+                number: self.number,
+                channel: self.channel,
+                off: self.off,
+                on: self.on,
+                behavior: self.behavior,
             }
+            .serialize(serializer),
         }
+    }
+}
 
-        struct Visitor;
+impl Prog {
+    /// Creates a program change on the global channel.
+    pub const fn new(number: MidiValue) -> Self {
+        Self {
+            number,
+            channel: Channel::Global,
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for Prog {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let cfg = PadCfg::new(VelocityCfg::Any, ModeCfg::NONE);
+        DeserializeConfig::deserialize(deserializer, &cfg)
+    }
+}
+
+/// Uses the default channel in `config` if the channel isn't specified.
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Prog {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &PadCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Number,
+            Channel,
+        }
+
+        struct Visitor<'a> {
+            cfg: &'a PadCfg<'a>,
+        }
+
+        impl Visitor<'_> {
+            fn prog(
+                &self,
+                number: MidiValue,
+                channel: Option<Channel>,
+            ) -> Prog {
+                Prog {
+                    number,
+                    channel: channel
+                        .or(self.cfg.defaults.channel)
+                        .unwrap_or(Channel::Global),
+                }
+            }
+        }
 
-        impl<'a> de::Visitor<'a> for Visitor {
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
             type Value = Prog;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "program chanel definition (integer or table)")
+                write!(
+                    f,
+                    "program change definition (integer, name, or table)"
+                )
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
-                Ok(Prog {
-                    number: MidiValue::deserialize(v.into_deserializer())?,
-                    channel: channel_default(),
-                })
+                let number = MidiValue::deserialize(v.into_deserializer())?;
+                Ok(self.prog(number, None))
             }
 
             fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
@@ -453,16 +663,69 @@ impl<'a> Deserialize<'a> for Prog {
                 }
             }
 
-            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let aliases = self.cfg.mode.aliases;
+                let number = ProgramNumber::deserialize(
+                    v.into_deserializer(),
+                    aliases,
+                )?;
+                Ok(self.prog(number.0, None))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: de::MapAccess<'a>,
             {
-                Fields::deserialize(MapAccessDeserializer::new(map))
-                    .map(Into::into)
+                let mut number = None;
+                let mut channel = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Number => {
+                            parse::check_dup(&number, "number")?;
+                            let seed = ConfigSeed::new(self.cfg.mode.aliases);
+                            number = Some(map.next_value_seed(seed)?);
+                        }
+                        Field::Channel => {
+                            parse::check_dup(&channel, "channel")?;
+                            channel = Some(map.next_value()?);
+                        }
+                    }
+                }
+                let number: ProgramNumber = number
+                    .ok_or_else(|| de::Error::missing_field("number"))?;
+                Ok(self.prog(number.0, channel))
             }
         }
 
-        deserializer.deserialize_map(Visitor)
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
+    }
+}
+
+impl Serialize for Prog {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields {
+            number: MidiValue,
+            #[serde(skip_serializing_if = "Channel::is_global")]
+            channel: Channel,
+        }
+
+        match self.channel {
+            Channel::Global => self.number.serialize(serializer),
+            channel => Fields {
+                number: self.number,
+                channel,
+            }
+            .serialize(serializer),
+        }
     }
 }
 
@@ -474,12 +737,127 @@ pub enum PadAction {
     Key(Keypress),
 }
 
+impl PadAction {
+    /// The key used for this action in a pad definition.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Note(_) => "note",
+            Self::Cc(_) => "cc",
+            Self::Prog(_) => "prog",
+            Self::Key(_) => "keypress",
+        }
+    }
+}
+
+/// Serializes as a table with a single key, as in a pad definition.
+impl Serialize for PadAction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            Self::Note(v) => map.serialize_entry(self.key(), v),
+            Self::Cc(v) => map.serialize_entry(self.key(), v),
+            Self::Prog(v) => map.serialize_entry(self.key(), v),
+            Self::Key(v) => map.serialize_entry(self.key(), v),
+        }?;
+        map.end()
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Pad {
     pub color: MidiValue,
     pub action: PadAction,
 }
 
+impl Serialize for Pad {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            color: MidiValue,
+            #[serde(flatten)]
+            action: &'a PadAction,
+        }
+
+        Fields {
+            color: self.color,
+            action: &self.action,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Omits the color if it is fixed by `config`.
+impl SerializeConfig<PadCfg<'_>> for Pad {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+        config: &PadCfg<'_>,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if config.color.is_none() {
+            return Serialize::serialize(self, serializer);
+        }
+        Serialize::serialize(&self.action, serializer)
+    }
+}
+
+impl Pad {
+    pub const fn note(color: MidiValue, note: Note) -> Self {
+        Self {
+            color,
+            action: PadAction::Note(note),
+        }
+    }
+
+    pub const fn cc(color: MidiValue, cc: Cc) -> Self {
+        Self {
+            color,
+            action: PadAction::Cc(cc),
+        }
+    }
+
+    pub const fn prog(color: MidiValue, prog: Prog) -> Self {
+        Self {
+            color,
+            action: PadAction::Prog(prog),
+        }
+    }
+
+    pub const fn keypress(color: MidiValue, key: Keypress) -> Self {
+        Self {
+            color,
+            action: PadAction::Key(key),
+        }
+    }
+
+    /// Checks that this pad is allowed by `config`, producing the same
+    /// errors as deserialization.
+    pub fn check<E: de::Error>(&self, config: &PadCfg<'_>) -> Result<(), E> {
+        let unknown = |name| {
+            E::custom(UnknownPadField {
+                name,
+                cfg: config,
+            })
+        };
+        if config.color.is_some_and(|c| c != self.color) {
+            return Err(unknown("color"));
+        }
+        match &self.action {
+            PadAction::Note(note) => note.velocity.check(&config.velocity),
+            PadAction::Key(_) if !config.keypress => Err(unknown("keypress")),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl Control for Pad {
     fn compile<W: Write>(
         &self,
@@ -533,23 +911,71 @@ impl Control for Pad {
         }
         .compile(writer)
     }
+
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        _config: &CompileCfg,
+    ) -> Result<Self, decompile::Error> {
+        let def = def::Definition::read(reader, address)?;
+        let channel = def.opt.channel();
+        let behavior = def.opt.behavior();
+        let action = match def.payload {
+            def::Payload::Note {
+                pitch,
+                velocity,
+            } => PadAction::Note(Note {
+                pitch,
+                channel,
+                velocity,
+                behavior,
+            }),
+            def::Payload::Cc {
+                number,
+                min,
+                max,
+            } => PadAction::Cc(Cc {
+                number,
+                channel,
+                off: min,
+                on: max,
+                behavior,
+            }),
+            def::Payload::Prog(number) => PadAction::Prog(Prog {
+                number,
+                channel,
+            }),
+            def::Payload::Key(code) => PadAction::Key(def.opt.key(code)),
+            def::Payload::CcFull(_) => {
+                return Err(decompile::Error::Control(address));
+            }
+        };
+        Ok(Self {
+            color: def.color,
+            action,
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
-pub struct PadCfg {
+pub struct PadCfg<'a> {
     keypress: bool,
     velocity: VelocityCfg,
     color: Option<MidiValue>,
     name: &'static str,
+    defaults: PadDefaults,
+    mode: ModeCfg<'a>,
 }
 
-impl PadCfg {
-    pub const fn new(velocity: VelocityCfg) -> Self {
+impl<'a> PadCfg<'a> {
+    pub const fn new(velocity: VelocityCfg, mode: ModeCfg<'a>) -> Self {
         Self {
             keypress: false,
             velocity,
             color: None,
             name: "pad",
+            defaults: PadDefaults::new(),
+            mode,
         }
     }
 
@@ -567,12 +993,18 @@ impl PadCfg {
         self.name = name;
         self
     }
+
+    /// Uses `defaults` for the fields that pads don't specify.
+    pub const fn defaults(mut self, defaults: PadDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
 }
 
-impl<'a> DeserializeConfig<'a, PadCfg> for Pad {
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Pad {
     fn deserialize<D>(
         deserializer: D,
-        config: &PadCfg,
+        config: &PadCfg<'_>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -589,12 +1021,44 @@ impl<'a> DeserializeConfig<'a, PadCfg> for Pad {
 
 struct OptionalPadCfg<'a> {
     required: bool,
-    pad: &'a PadCfg,
+    pad: &'a PadCfg<'a>,
 }
 
 impl OptionalPadCfg<'_> {
-    fn note(&self) -> NoteCfg {
-        NoteCfg::new(self.pad.velocity)
+    fn note(&self) -> NoteCfg<'_> {
+        NoteCfg::new(self.pad.velocity, self.pad.mode)
+            .defaults(self.pad.defaults)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct UnknownPadField<'a> {
+    name: &'a str,
+    cfg: &'a PadCfg<'a>,
+}
+
+impl Display for UnknownPadField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name == "color" {
+            return write!(
+                f,
+                "the color of this {} cannot be customized",
+                self.cfg.name,
+            );
+        }
+        let allowed = [
+            self.cfg.color.is_none().then_some("color"),
+            Some("note"),
+            Some("cc"),
+            Some("prog"),
+            self.cfg.keypress.then_some("keypress"),
+        ];
+        write!(
+            f,
+            "unknown key `{}`; expected {}",
+            self.name.escape_default(),
+            parse::one_of(allowed.into_iter().flatten()),
+        )
     }
 }
 
@@ -614,39 +1078,8 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
             Keypress,
         }
 
-        #[derive(Clone, Copy)]
-        struct UnknownField<'a> {
-            name: &'a str,
-            cfg: &'a PadCfg,
-        }
-
-        impl Display for UnknownField<'_> {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                if self.name == "color" {
-                    return write!(
-                        f,
-                        "the color of this {} cannot be customized",
-                        self.cfg.name,
-                    );
-                }
-                let allowed = [
-                    self.cfg.color.is_none().then_some("color"),
-                    Some("note"),
-                    Some("cc"),
-                    Some("prog"),
-                    self.cfg.keypress.then_some("keypress"),
-                ];
-                write!(
-                    f,
-                    "unknown key `{}`; expected {}",
-                    self.name.escape_default(),
-                    parse::one_of(allowed.into_iter().flatten()),
-                )
-            }
-        }
-
         struct FieldVisitor<'a> {
-            cfg: &'a PadCfg,
+            cfg: &'a PadCfg<'a>,
         }
 
         impl de::Visitor<'_> for FieldVisitor<'_> {
@@ -666,7 +1099,7 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
                     "cc" => Ok(Field::Cc),
                     "prog" => Ok(Field::Prog),
                     "keypress" if self.cfg.keypress => Ok(Field::Keypress),
-                    _ => Err(E::custom(UnknownField {
+                    _ => Err(E::custom(UnknownPadField {
                         name: v,
                         cfg: self.cfg,
                     })),
@@ -674,10 +1107,10 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
             }
         }
 
-        impl<'a> DeserializeConfig<'a, PadCfg> for Field {
+        impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Field {
             fn deserialize<D>(
                 deserializer: D,
-                config: &PadCfg,
+                config: &PadCfg<'_>,
             ) -> Result<Self, D::Error>
             where
                 D: Deserializer<'a>,
@@ -696,12 +1129,7 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
 
         impl Display for DuplicateAction<'_> {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                let old = match self.old {
-                    PadAction::Note(_) => "note",
-                    PadAction::Cc(_) => "cc",
-                    PadAction::Prog(_) => "prog",
-                    PadAction::Key(_) => "keypress",
-                };
+                let old = self.old.key();
                 let new = match self.new {
                     Field::Note => "note",
                     Field::Cc => "cc",
@@ -720,7 +1148,7 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
 
         #[derive(Clone, Copy)]
         struct MissingAction<'a> {
-            cfg: &'a PadCfg,
+            cfg: &'a PadCfg<'a>,
         }
 
         impl Display for MissingAction<'_> {
@@ -773,7 +1201,14 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
                     match field {
                         Field::Color => {
                             parse::check_dup(&color, "color")?;
-                            color = Some(map.next_value()?);
+                            color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(
+                                        &self.cfg.pad.mode,
+                                    ),
+                                )?
+                                .0,
+                            );
                         }
                         Field::Note => {
                             check_action()?;
@@ -784,11 +1219,15 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
                         }
                         Field::Cc => {
                             check_action()?;
-                            action = Some(PadAction::Cc(map.next_value()?));
+                            let seed = ConfigSeed::new(self.cfg.pad);
+                            let cc = map.next_value_seed(seed)?;
+                            action = Some(PadAction::Cc(cc));
                         }
                         Field::Prog => {
                             check_action()?;
-                            action = Some(PadAction::Prog(map.next_value()?));
+                            let seed = ConfigSeed::new(self.cfg.pad);
+                            let prog = map.next_value_seed(seed)?;
+                            action = Some(PadAction::Prog(prog));
                         }
                         Field::Keypress => {
                             check_action()?;
@@ -801,7 +1240,9 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
                 }
                 let missing = de::Error::missing_field;
                 Ok(Optional::Some(Pad {
-                    color: color.ok_or_else(|| missing("color"))?,
+                    color: color
+                        .or(self.cfg.pad.defaults.color)
+                        .ok_or_else(|| missing("color"))?,
                     action: action.ok_or_else(|| {
                         de::Error::custom(MissingAction {
                             cfg: self.cfg.pad,
@@ -843,10 +1284,10 @@ impl<'a> DeserializeConfig<'a, OptionalPadCfg<'_>> for Optional<Pad> {
     }
 }
 
-impl<'a> DeserializeConfig<'a, PadCfg> for Optional<Pad> {
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Optional<Pad> {
     fn deserialize<D>(
         deserializer: D,
-        config: &PadCfg,
+        config: &PadCfg<'_>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -857,3 +1298,169 @@ impl<'a> DeserializeConfig<'a, PadCfg> for Optional<Pad> {
         })
     }
 }
+
+/// Values for the fields that pads don't specify, from the `pad` table in a
+/// map's `[defaults]`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PadDefaults {
+    pub color: Option<MidiValue>,
+    pub channel: Option<Channel>,
+    pub behavior: Option<Behavior>,
+    pub velocity: Option<Velocity>,
+}
+
+impl PadDefaults {
+    pub const fn new() -> Self {
+        Self {
+            color: None,
+            channel: None,
+            behavior: None,
+            velocity: None,
+        }
+    }
+}
+
+/// Accepts only the fields that pads allowed by `config` may specify.
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for PadDefaults {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &PadCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        enum Field {
+            Color,
+            Channel,
+            Behavior,
+            Velocity,
+        }
+
+        #[derive(Clone, Copy)]
+        struct UnknownField<'a> {
+            name: &'a str,
+            cfg: &'a PadCfg<'a>,
+        }
+
+        impl Display for UnknownField<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if self.name == "color" {
+                    return write!(
+                        f,
+                        "the color of this {} cannot be customized",
+                        self.cfg.name,
+                    );
+                }
+                let allowed = [
+                    self.cfg.color.is_none().then_some("color"),
+                    Some("channel"),
+                    Some("behavior"),
+                    Some("velocity"),
+                ];
+                write!(
+                    f,
+                    "unknown key `{}`; expected {}",
+                    self.name.escape_default(),
+                    parse::one_of(allowed.into_iter().flatten()),
+                )
+            }
+        }
+
+        struct FieldVisitor<'a> {
+            cfg: &'a PadCfg<'a>,
+        }
+
+        impl de::Visitor<'_> for FieldVisitor<'_> {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "key")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v {
+                    "color" if self.cfg.color.is_none() => Ok(Field::Color),
+                    "channel" => Ok(Field::Channel),
+                    "behavior" => Ok(Field::Behavior),
+                    "velocity" => Ok(Field::Velocity),
+                    _ => Err(E::custom(UnknownField {
+                        name: v,
+                        cfg: self.cfg,
+                    })),
+                }
+            }
+        }
+
+        impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Field {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &PadCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'a>,
+            {
+                deserializer.deserialize_str(FieldVisitor {
+                    cfg: config,
+                })
+            }
+        }
+
+        struct Visitor<'a> {
+            cfg: &'a PadCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = PadDefaults;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} defaults (table)", self.cfg.name)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut defaults = PadDefaults::new();
+                while let Some(field) =
+                    map.next_key_seed(ConfigSeed::new(self.cfg))?
+                {
+                    match field {
+                        Field::Color => {
+                            parse::check_dup(&defaults.color, "color")?;
+                            defaults.color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(
+                                        &self.cfg.mode,
+                                    ),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::Channel => {
+                            parse::check_dup(&defaults.channel, "channel")?;
+                            defaults.channel = Some(map.next_value()?);
+                        }
+                        Field::Behavior => {
+                            parse::check_dup(&defaults.behavior, "behavior")?;
+                            defaults.behavior = Some(map.next_value()?);
+                        }
+                        Field::Velocity => {
+                            parse::check_dup(&defaults.velocity, "velocity")?;
+                            let seed = ConfigSeed::new(&self.cfg.velocity);
+                            defaults.velocity =
+                                Some(map.next_value_seed(seed)?);
+                        }
+                    }
+                }
+                Ok(defaults)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
+    }
+}
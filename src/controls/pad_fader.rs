@@ -19,14 +19,18 @@
 
 use super::def;
 use super::{CompileCfg, Control, Optional};
-use crate::common::{Channel, MidiValue};
+use crate::common::{CcNumber, Channel, Color, MidiValue, ModeCfg};
+use crate::decompile::{self, Reader};
 use crate::parse;
-use serde::Deserialize;
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
 use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io::{self, Write};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Orientation {
     Horizontal,
     Vertical,
@@ -62,12 +66,19 @@ impl<'a> Deserialize<'a> for Orientation {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Mode {
     Unipolar,
     Bipolar,
 }
 
+impl Mode {
+    fn is_unipolar(&self) -> bool {
+        *self == Self::Unipolar
+    }
+}
+
 impl<'a> Deserialize<'a> for Mode {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -107,6 +118,23 @@ pub struct Fader {
     pub channel: Channel,
 }
 
+impl Fader {
+    /// Creates a unipolar fader on the global channel.
+    pub const fn new(
+        orientation: Orientation,
+        color: MidiValue,
+        cc: MidiValue,
+    ) -> Self {
+        Self {
+            orientation,
+            mode: Mode::Unipolar,
+            color,
+            cc,
+            channel: Channel::Global,
+        }
+    }
+}
+
 impl Control for Fader {
     fn compile<W: Write>(
         &self,
@@ -125,10 +153,92 @@ impl Control for Fader {
         }
         .compile(writer)
     }
+
+    /// Reads a fader definition. Because the orientation of faders isn't
+    /// stored in their definitions, the returned fader is always horizontal;
+    /// the caller should set the correct orientation.
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        _config: &CompileCfg,
+    ) -> Result<Self, decompile::Error> {
+        let def = def::Definition::read(reader, address)?;
+        let def::Payload::CcFull(cc) = def.payload else {
+            return Err(decompile::Error::Control(address));
+        };
+        Ok(Self {
+            orientation: Orientation::Horizontal,
+            mode: def.opt.pad_fader(),
+            color: def.color,
+            cc,
+            channel: def.opt.channel(),
+        })
+    }
+}
+
+impl Serialize for Fader {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields {
+            orientation: Orientation,
+            #[serde(skip_serializing_if = "Mode::is_unipolar")]
+            mode: Mode,
+            color: MidiValue,
+            cc: MidiValue,
+            #[serde(skip_serializing_if = "Channel::is_global")]
+            channel: Channel,
+        }
+
+        Fields {
+            orientation: self.orientation,
+            mode: self.mode,
+            color: self.color,
+            cc: self.cc,
+            channel: self.channel,
+        }
+        .serialize(serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for Optional<Fader> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let cfg = FaderCfg::new(ModeCfg::NONE);
+        DeserializeConfig::deserialize(deserializer, &cfg)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FaderCfg<'a> {
+    defaults: FaderDefaults,
+    mode: ModeCfg<'a>,
+}
+
+impl<'a> FaderCfg<'a> {
+    pub const fn new(mode: ModeCfg<'a>) -> Self {
+        Self {
+            defaults: FaderDefaults::new(),
+            mode,
+        }
+    }
+
+    /// Uses `defaults` for the fields that faders don't specify.
+    pub const fn defaults(mut self, defaults: FaderDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+}
+
+impl<'a> DeserializeConfig<'a, FaderCfg<'_>> for Optional<Fader> {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &FaderCfg<'_>,
+    ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
@@ -143,9 +253,11 @@ impl<'a> Deserialize<'a> for Optional<Fader> {
             Channel,
         }
 
-        struct Visitor;
+        struct Visitor<'a> {
+            cfg: &'a FaderCfg<'a>,
+        }
 
-        impl<'a> de::Visitor<'a> for Visitor {
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
             type Value = Optional<Fader>;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -175,11 +287,21 @@ impl<'a> Deserialize<'a> for Optional<Fader> {
                         }
                         Field::Color => {
                             parse::check_dup(&color, "color")?;
-                            color = Some(map.next_value()?);
+                            color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(
+                                        &self.cfg.mode,
+                                    ),
+                                )?
+                                .0,
+                            );
                         }
                         Field::Cc => {
                             parse::check_dup(&cc, "cc")?;
-                            cc = Some(map.next_value()?);
+                            let aliases = self.cfg.mode.aliases;
+                            let number: CcNumber =
+                                map.next_value_seed(ConfigSeed::new(aliases))?;
+                            cc = Some(number.0);
                         }
                         Field::Channel => {
                             parse::check_dup(&channel, "channel")?;
@@ -191,13 +313,19 @@ impl<'a> Deserialize<'a> for Optional<Fader> {
                     return Ok(Optional::None);
                 }
                 let missing = de::Error::missing_field;
+                let defaults = &self.cfg.defaults;
                 Ok(Optional::Some(Fader {
                     orientation: orientation
+                        .or(defaults.orientation)
                         .ok_or_else(|| missing("orientation"))?,
-                    mode: mode.unwrap_or(Mode::Unipolar),
-                    color: color.ok_or_else(|| missing("color"))?,
+                    mode: mode.or(defaults.mode).unwrap_or(Mode::Unipolar),
+                    color: color
+                        .or(defaults.color)
+                        .ok_or_else(|| missing("color"))?,
                     cc: cc.ok_or_else(|| missing("cc"))?,
-                    channel: channel.unwrap_or(Channel::Global),
+                    channel: channel
+                        .or(defaults.channel)
+                        .unwrap_or(Channel::Global),
                 }))
             }
 
@@ -219,6 +347,101 @@ impl<'a> Deserialize<'a> for Optional<Fader> {
             }
         }
 
-        deserializer.deserialize_map(Visitor)
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
+    }
+}
+
+/// Values for the fields that faders don't specify, from the `fader` table in
+/// a map's `[defaults]`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FaderDefaults {
+    pub orientation: Option<Orientation>,
+    pub mode: Option<Mode>,
+    pub color: Option<MidiValue>,
+    pub channel: Option<Channel>,
+}
+
+impl FaderDefaults {
+    pub const fn new() -> Self {
+        Self {
+            orientation: None,
+            mode: None,
+            color: None,
+            channel: None,
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, FaderCfg<'_>> for FaderDefaults {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &FaderCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Orientation,
+            Mode,
+            Color,
+            Channel,
+        }
+
+        struct Visitor<'a> {
+            cfg: &'a FaderCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = FaderDefaults;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "fader defaults (table)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut defaults = FaderDefaults::new();
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Orientation => {
+                            let orientation = &defaults.orientation;
+                            parse::check_dup(orientation, "orientation")?;
+                            defaults.orientation = Some(map.next_value()?);
+                        }
+                        Field::Mode => {
+                            parse::check_dup(&defaults.mode, "mode")?;
+                            defaults.mode = Some(map.next_value()?);
+                        }
+                        Field::Color => {
+                            parse::check_dup(&defaults.color, "color")?;
+                            defaults.color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(
+                                        &self.cfg.mode,
+                                    ),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::Channel => {
+                            parse::check_dup(&defaults.channel, "channel")?;
+                            defaults.channel = Some(map.next_value()?);
+                        }
+                    }
+                }
+                Ok(defaults)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
     }
 }
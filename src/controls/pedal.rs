@@ -19,17 +19,76 @@
 
 use super::def;
 use super::{CompileCfg, Control};
-use crate::common::{Channel, MidiValue};
-use serde::Deserialize;
+use crate::aliases::Aliases;
+use crate::common::{CcNumber, Channel, MidiValue};
+use crate::decompile::{self, Reader};
+use crate::parse;
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::{self, Write};
 
-#[derive(Clone, Copy, Debug, Deserialize)]
-#[serde(deny_unknown_fields)]
-#[serde(expecting = "pedal definition (table)")]
+#[derive(Clone, Copy, Debug, Serialize)]
 pub struct Pedal {
     pub cc: MidiValue,
 }
 
+/// Uses `config` for names from `[aliases.cc]`.
+impl<'a> DeserializeConfig<'a, Aliases> for Pedal {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &Aliases,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Cc,
+        }
+
+        struct Visitor<'a> {
+            aliases: &'a Aliases,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = Pedal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "pedal definition (table)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut cc = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Cc => {
+                            parse::check_dup(&cc, "cc")?;
+                            let seed = ConfigSeed::new(self.aliases);
+                            cc = Some(map.next_value_seed(seed)?);
+                        }
+                    }
+                }
+                let cc: CcNumber =
+                    cc.ok_or_else(|| de::Error::missing_field("cc"))?;
+                Ok(Pedal {
+                    cc: cc.0,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            aliases: config,
+        })
+    }
+}
+
 impl Control for Pedal {
     fn compile<W: Write>(
         &self,
@@ -49,4 +108,20 @@ impl Control for Pedal {
         }
         .compile(writer)
     }
+
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        _config: &CompileCfg,
+    ) -> Result<Self, decompile::Error> {
+        match def::Definition::read(reader, address)?.payload {
+            def::Payload::Cc {
+                number,
+                ..
+            } => Ok(Self {
+                cc: number,
+            }),
+            _ => Err(decompile::Error::Control(address)),
+        }
+    }
 }
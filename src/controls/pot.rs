@@ -19,11 +19,13 @@
 
 use super::def;
 use super::{CompileCfg, Control, Optional};
-use crate::common::{Channel, MidiValue};
+use crate::common::{CcNumber, Channel, MidiValue, ModeCfg};
+use crate::decompile::{self, Reader};
 use crate::parse;
 use crate::parse::config::{ConfigSeed, DeserializeConfig};
-use serde::Deserialize;
 use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io::{self, Write};
 
@@ -34,6 +36,25 @@ pub enum Pickup {
     No,
 }
 
+impl Pickup {
+    fn is_global(&self) -> bool {
+        *self == Self::Global
+    }
+}
+
+impl Serialize for Pickup {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Global => serializer.serialize_str("global"),
+            Self::Yes => serializer.serialize_bool(true),
+            Self::No => serializer.serialize_bool(false),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PickupCfg {
     GlobalAllowed,
@@ -109,6 +130,33 @@ pub struct Pot {
     pub pickup: Pickup,
 }
 
+impl Pot {
+    /// Creates a pot spanning the full range of CC values on the global
+    /// channel.
+    pub const fn new(cc: MidiValue) -> Self {
+        Self {
+            channel: Channel::Global,
+            cc,
+            min: MidiValue::MIN,
+            max: MidiValue::MAX,
+            pickup: Pickup::Global,
+        }
+    }
+
+    /// Checks that this pot is allowed by `config`, producing the same
+    /// errors as deserialization. A global pickup setting is checked as if
+    /// it had been specified with `pickup = "global"`.
+    pub fn check<E: de::Error>(&self, config: &PotCfg<'_>) -> Result<(), E> {
+        if self.pickup == Pickup::Global && !config.pickup.global_allowed() {
+            return Err(E::invalid_value(
+                de::Unexpected::Str("global"),
+                &"`true` or `false`",
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl Control for Pot {
     fn compile<W: Write>(
         &self,
@@ -131,19 +179,83 @@ impl Control for Pot {
         }
         .compile(writer)
     }
+
+    fn decompile(
+        address: u8,
+        reader: &mut Reader<'_>,
+        _config: &CompileCfg,
+    ) -> Result<Self, decompile::Error> {
+        let def = def::Definition::read(reader, address)?;
+        let def::Payload::Cc {
+            number,
+            min,
+            max,
+        } = def.payload
+        else {
+            return Err(decompile::Error::Control(address));
+        };
+        Ok(Self {
+            channel: def.opt.channel(),
+            cc: number,
+            min,
+            max,
+            pickup: def.opt.pickup(),
+        })
+    }
+}
+
+impl Serialize for Pot {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn is_min(v: &MidiValue) -> bool {
+            *v == MidiValue::MIN
+        }
+
+        fn is_max(v: &MidiValue) -> bool {
+            *v == MidiValue::MAX
+        }
+
+        #[derive(Serialize)]
+        struct Fields {
+            cc: MidiValue,
+            #[serde(skip_serializing_if = "Channel::is_global")]
+            channel: Channel,
+            #[serde(skip_serializing_if = "is_min")]
+            min: MidiValue,
+            #[serde(skip_serializing_if = "is_max")]
+            max: MidiValue,
+            #[serde(skip_serializing_if = "Pickup::is_global")]
+            pickup: Pickup,
+        }
+
+        Fields {
+            cc: self.cc,
+            channel: self.channel,
+            min: self.min,
+            max: self.max,
+            pickup: self.pickup,
+        }
+        .serialize(serializer)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct PotCfg {
+pub struct PotCfg<'a> {
     pickup: PickupCfg,
     name: &'static str,
+    defaults: PotDefaults,
+    mode: ModeCfg<'a>,
 }
 
-impl PotCfg {
-    pub const fn new() -> Self {
+impl<'a> PotCfg<'a> {
+    pub const fn new(mode: ModeCfg<'a>) -> Self {
         Self {
             pickup: PickupCfg::BinaryOnly,
             name: "pot",
+            defaults: PotDefaults::new(),
+            mode,
         }
     }
 
@@ -156,12 +268,18 @@ impl PotCfg {
         self.name = name;
         self
     }
+
+    /// Uses `defaults` for the fields that pots don't specify.
+    pub const fn defaults(mut self, defaults: PotDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
 }
 
-impl<'a> DeserializeConfig<'a, PotCfg> for Pot {
+impl<'a> DeserializeConfig<'a, PotCfg<'_>> for Pot {
     fn deserialize<D>(
         deserializer: D,
-        config: &PotCfg,
+        config: &PotCfg<'_>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -178,7 +296,7 @@ impl<'a> DeserializeConfig<'a, PotCfg> for Pot {
 
 struct OptionalPotCfg<'a> {
     required: bool,
-    pot: &'a PotCfg,
+    pot: &'a PotCfg<'a>,
 }
 
 impl OptionalPotCfg<'_> {
@@ -236,7 +354,10 @@ impl<'a> DeserializeConfig<'a, OptionalPotCfg<'_>> for Optional<Pot> {
                         }
                         Field::Cc => {
                             parse::check_dup(&cc, "cc")?;
-                            cc = Some(map.next_value()?);
+                            let aliases = self.cfg.pot.mode.aliases;
+                            let number: CcNumber =
+                                map.next_value_seed(ConfigSeed::new(aliases))?;
+                            cc = Some(number.0);
                         }
                         Field::Min => {
                             parse::check_dup(&min, "min")?;
@@ -257,17 +378,22 @@ impl<'a> DeserializeConfig<'a, OptionalPotCfg<'_>> for Optional<Pot> {
                     return Ok(Optional::None);
                 }
                 let missing = de::Error::missing_field;
+                let defaults = &self.cfg.pot.defaults;
                 Ok(Optional::Some(Pot {
-                    channel: channel.unwrap_or(Channel::Global),
+                    channel: channel
+                        .or(defaults.channel)
+                        .unwrap_or(Channel::Global),
                     cc: cc.ok_or_else(|| missing("cc"))?,
-                    min: min.unwrap_or(MidiValue::MIN),
-                    max: max.unwrap_or(MidiValue::MAX),
+                    min: min.or(defaults.min).unwrap_or(MidiValue::MIN),
+                    max: max.or(defaults.max).unwrap_or(MidiValue::MAX),
                     // Use `Global` as the default even on devices that don't
                     // have a global pickup setting; this matches the behavior
                     // of the official software, which doesn't provide a global
                     // pickup option for pots yet still initializes them in
                     // that state.
-                    pickup: pickup.unwrap_or(Pickup::Global),
+                    pickup: pickup
+                        .or(defaults.pickup)
+                        .unwrap_or(Pickup::Global),
                 }))
             }
 
@@ -304,10 +430,10 @@ impl<'a> DeserializeConfig<'a, OptionalPotCfg<'_>> for Optional<Pot> {
     }
 }
 
-impl<'a> DeserializeConfig<'a, PotCfg> for Optional<Pot> {
+impl<'a> DeserializeConfig<'a, PotCfg<'_>> for Optional<Pot> {
     fn deserialize<D>(
         deserializer: D,
-        config: &PotCfg,
+        config: &PotCfg<'_>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -318,3 +444,89 @@ impl<'a> DeserializeConfig<'a, PotCfg> for Optional<Pot> {
         })
     }
 }
+
+/// Values for the fields that pots don't specify, from the `pot` (or
+/// `fader`) table in a map's `[defaults]`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PotDefaults {
+    pub channel: Option<Channel>,
+    pub min: Option<MidiValue>,
+    pub max: Option<MidiValue>,
+    pub pickup: Option<Pickup>,
+}
+
+impl PotDefaults {
+    pub const fn new() -> Self {
+        Self {
+            channel: None,
+            min: None,
+            max: None,
+            pickup: None,
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, PotCfg<'_>> for PotDefaults {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &PotCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Channel,
+            Min,
+            Max,
+            Pickup,
+        }
+
+        struct Visitor<'a> {
+            cfg: &'a PotCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = PotDefaults;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} defaults (table)", self.cfg.name)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut defaults = PotDefaults::new();
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Channel => {
+                            parse::check_dup(&defaults.channel, "channel")?;
+                            defaults.channel = Some(map.next_value()?);
+                        }
+                        Field::Min => {
+                            parse::check_dup(&defaults.min, "min")?;
+                            defaults.min = Some(map.next_value()?);
+                        }
+                        Field::Max => {
+                            parse::check_dup(&defaults.max, "max")?;
+                            defaults.max = Some(map.next_value()?);
+                        }
+                        Field::Pickup => {
+                            parse::check_dup(&defaults.pickup, "pickup")?;
+                            let seed = ConfigSeed::new(&self.cfg.pickup);
+                            defaults.pickup = Some(map.next_value_seed(seed)?);
+                        }
+                    }
+                }
+                Ok(defaults)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            cfg: config,
+        })
+    }
+}
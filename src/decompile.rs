@@ -0,0 +1,252 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::common::{MidiValue, Name};
+use super::launchkey::flkey as flk;
+use super::launchkey::flkey_mini as flkm;
+use super::launchkey::launchkey_mini_mk3 as lkmm3;
+use super::launchkey::launchkey_mk3 as lkm3;
+use super::launchpad::launchpad_mini_mk3 as lpmm3;
+use super::launchpad::launchpad_x as lpx;
+use super::{CustomMode, Device};
+use serde::Deserialize;
+use std::fmt::{self, Display};
+
+/// Offset of the device ID in the SysEx header.
+const DEVICE_ID_OFFSET: usize = 5;
+/// Offset of the map type in Launchkey custom modes.
+const MAP_TYPE_OFFSET: usize = 9;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The data ended before the custom mode was complete.
+    Eof,
+    /// An unexpected byte was found at the given offset.
+    Unexpected {
+        offset: usize,
+        byte: u8,
+    },
+    /// The device ID is not recognized.
+    UnknownDevice(u8),
+    /// The map type of a Launchkey custom mode is not recognized.
+    UnknownMapType {
+        device: u8,
+        map_type: u8,
+    },
+    /// The control at the given address uses an unsupported configuration.
+    Control(u8),
+    /// Extra data follows the end of the SysEx message.
+    Trailing(usize),
+    /// The custom mode can't be represented exactly as a TOML file.
+    Lossy,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "unexpected end of data"),
+            Self::Unexpected {
+                offset,
+                byte,
+            } => {
+                write!(f, "unexpected byte 0x{byte:02x} at offset {offset}")
+            }
+            Self::UnknownDevice(id) => {
+                write!(f, "unknown device ID 0x{id:02x}")
+            }
+            Self::UnknownMapType {
+                device,
+                map_type,
+            } => {
+                write!(f, "unknown map type 0x{map_type:02x} ")?;
+                write!(f, "for device ID 0x{device:02x}")
+            }
+            Self::Control(addr) => write!(
+                f,
+                "unsupported definition for control at address 0x{addr:02x}",
+            ),
+            Self::Trailing(offset) => {
+                write!(
+                    f,
+                    "unexpected data after end of SysEx at offset {offset}"
+                )
+            }
+            Self::Lossy => {
+                write!(f, "custom mode cannot be represented exactly in TOML")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Reads the bytes of a compiled custom mode.
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+        }
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.offset).copied()
+    }
+
+    pub fn byte(&mut self) -> Result<u8, Error> {
+        let b = self.peek().ok_or(Error::Eof)?;
+        self.offset += 1;
+        Ok(b)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.iter_mut().try_for_each(|b| {
+            *b = self.byte()?;
+            Ok(())
+        })?;
+        Ok(array)
+    }
+
+    /// Ensures the next bytes are equal to `expected`.
+    pub fn expect(&mut self, expected: &[u8]) -> Result<(), Error> {
+        expected.iter().try_for_each(|&b| {
+            let offset = self.offset;
+            match self.byte()? {
+                byte if byte == b => Ok(()),
+                byte => Err(Error::Unexpected {
+                    offset,
+                    byte,
+                }),
+            }
+        })
+    }
+
+    pub fn value(&mut self) -> Result<MidiValue, Error> {
+        let offset = self.offset;
+        let byte = self.byte()?;
+        MidiValue::new(byte).ok_or(Error::Unexpected {
+            offset,
+            byte,
+        })
+    }
+
+    pub fn name(&mut self) -> Result<Name, Error> {
+        let offset = self.offset;
+        let len = self.byte()?;
+        let bytes = self.bytes.get(self.offset..).unwrap_or_default();
+        let bytes = bytes.get(..len.into()).ok_or(Error::Eof)?;
+        let name = std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(Error::Unexpected {
+                offset,
+                byte: len,
+            })?;
+        self.offset += bytes.len();
+        Ok(name)
+    }
+
+    /// Ensures there is no data after the end of the custom mode.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.offset < self.bytes.len() {
+            Err(Error::Trailing(self.offset))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Determines which device (and map, for Launchkey devices) a compiled
+/// custom mode is for.
+pub fn identify(bytes: &[u8]) -> Result<Device, Error> {
+    let id = *bytes.get(DEVICE_ID_OFFSET).ok_or(Error::Eof)?;
+    let map_type = || bytes.get(MAP_TYPE_OFFSET).copied().ok_or(Error::Eof);
+    let unknown = |map_type| Error::UnknownMapType {
+        device: id,
+        map_type,
+    };
+    // Several maps for a Launchkey or FLkey device form a combined map.
+    let combined = bytes
+        .iter()
+        .position(|&b| b == 0xf7)
+        .is_some_and(|end| end + 1 < bytes.len());
+    Ok(match id {
+        lpx::DEVICE_ID => Device::LaunchpadX,
+        lpmm3::DEVICE_ID => Device::LaunchpadMiniMk3,
+        lkmm3::DEVICE_ID if combined => Device::LaunchkeyMiniMk3,
+        lkm3::DEVICE_ID if combined => Device::LaunchkeyMk3,
+        flkm::DEVICE_ID if combined => Device::FlkeyMini,
+        flk::DEVICE_ID if combined => Device::Flkey,
+        lkmm3::DEVICE_ID => match map_type()? {
+            0x00 => Device::LaunchkeyMiniMk3Pots,
+            0x01 => Device::LaunchkeyMiniMk3Pads,
+            0x02 => Device::LaunchkeyMiniMk3Pedal,
+            t => return Err(unknown(t)),
+        },
+        lkm3::DEVICE_ID => match map_type()? {
+            0x00 => Device::LaunchkeyMk3Pots,
+            0x01 => Device::LaunchkeyMk3Pads,
+            0x02 => Device::LaunchkeyMk3Pedal,
+            0x03 => Device::LaunchkeyMk3Faders,
+            t => return Err(unknown(t)),
+        },
+        flkm::DEVICE_ID => match map_type()? {
+            0x00 => Device::FlkeyMiniPots,
+            0x01 => Device::FlkeyMiniPads,
+            0x02 => Device::FlkeyMiniPedal,
+            t => return Err(unknown(t)),
+        },
+        flk::DEVICE_ID => match map_type()? {
+            0x00 => Device::FlkeyPots,
+            0x01 => Device::FlkeyPads,
+            0x02 => Device::FlkeyPedal,
+            0x03 => Device::FlkeyFaders,
+            t => return Err(unknown(t)),
+        },
+        _ => return Err(Error::UnknownDevice(id)),
+    })
+}
+
+/// Decompiles a custom mode into the text of an equivalent TOML file.
+///
+/// The resulting TOML is compiled again and checked against `bytes`, so
+/// successful decompilation guarantees an exact round trip.
+pub fn decompile(bytes: &[u8]) -> Result<String, Error> {
+    let device = identify(bytes)?;
+    let mut reader = Reader::new(bytes);
+    let mode = CustomMode::decompile(device, &mut reader)?;
+    reader.finish()?;
+
+    let toml = mode.to_toml().map_err(|_| Error::Lossy)?;
+    let mode = CustomMode::deserialize(toml::Deserializer::new(&toml))
+        .map_err(|_| Error::Lossy)?;
+    let mut compiled = Vec::new();
+    mode.compile(&mut compiled).expect("writing to Vec failed");
+    if compiled != bytes {
+        return Err(Error::Lossy);
+    }
+    Ok(toml)
+}
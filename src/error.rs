@@ -24,7 +24,8 @@ use serde::de;
 use std::fmt::{self, Display};
 use std::io::{self, Write};
 use std::ops::Range;
-use toml::de::Error as TomlError;
+use std::path::PathBuf;
+use toml_edit::de::Error as TomlError;
 
 /// Gets the first line of `s`, up to but not including `'\n'`.
 ///
@@ -204,41 +205,183 @@ impl de::Error for DeserializationError {
     }
 }
 
-pub struct Error {
+/// An error encountered while parsing a custom mode.
+#[derive(Debug)]
+pub struct Error(Box<ErrorInner>);
+
+#[derive(Debug)]
+struct ErrorInner {
     de: Option<DeserializationError>,
-    toml: TomlError,
+    toml: Option<TomlError>,
+    span: Option<Range<usize>>,
+    /// The file the error occurred in, if it isn't the file being parsed
+    /// (i.e., it was named by `extends`).
+    file: Option<File>,
+}
+
+#[derive(Debug)]
+struct File {
+    path: PathBuf,
+    text: String,
 }
 
 impl Error {
     pub fn new(de: Option<DeserializationError>, toml: TomlError) -> Self {
-        Self {
+        Self(Box::new(ErrorInner {
             de,
-            toml,
+            span: toml.span(),
+            toml: Some(toml),
+            file: None,
+        }))
+    }
+
+    pub(crate) fn custom<T: Display>(
+        msg: T,
+        span: Option<Range<usize>>,
+    ) -> Self {
+        Self(Box::new(ErrorInner {
+            de: Some(de::Error::custom(msg)),
+            toml: None,
+            span,
+            file: None,
+        }))
+    }
+
+    pub(crate) fn with_span(mut self, span: Range<usize>) -> Self {
+        self.0.span = Some(span);
+        self
+    }
+
+    /// Marks this error as having occurred in the file at `path`, whose
+    /// contents are `text`. Spans are relative to `offset`.
+    pub(crate) fn in_file(
+        mut self,
+        path: PathBuf,
+        text: String,
+        offset: usize,
+    ) -> Self {
+        let inner = &mut *self.0;
+        if let Some(span) = &mut inner.span {
+            span.start -= offset;
+            span.end -= offset;
         }
+        inner.file = Some(File {
+            path,
+            text,
+        });
+        self
     }
 
+    /// The span of the error within the file it occurred in.
+    ///
+    /// This is usually the file being parsed, but it may be a file that it
+    /// extends; see [`Self::show`].
     pub fn span(&self) -> Option<Range<usize>> {
-        self.toml.span()
+        self.0.span.clone()
     }
 
+    /// Shows the error, along with the part of the input it refers to.
+    ///
+    /// If the error occurred in a file named by `extends`, that file is shown
+    /// instead of `input`.
     pub fn show<W: Write>(
         &self,
         writer: &mut AnsiWriter<W>,
         input: Input<'_>,
     ) -> io::Result<()> {
-        show(self, writer, input)
+        show(self, writer, self.input(input), Severity::Error)
+    }
+
+    /// The input the error occurred in: `input`, or the file named by
+    /// `extends` that it occurred in.
+    fn input<'a>(&'a self, input: Input<'a>) -> Input<'a> {
+        match &self.0.file {
+            Some(file) => Input {
+                path: &file.path,
+                text: &file.text,
+            },
+            None => input,
+        }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(e) = &self.de {
-            write!(f, "{e}")
-        } else {
-            write!(f, "TOML syntax error")?;
-            let mut lines = self.toml.message().lines();
-            lines.by_ref().take(2).try_for_each(|s| write!(f, ": {s}"))?;
-            lines.try_for_each(|s| write!(f, "\n{s}"))
+        match (&self.0.de, &self.0.toml) {
+            (Some(e), _) => write!(f, "{e}"),
+            (None, Some(e)) => {
+                write!(f, "TOML syntax error")?;
+                let mut lines = e.message().lines();
+                lines.by_ref().take(2).try_for_each(|s| write!(f, ": {s}"))?;
+                lines.try_for_each(|s| write!(f, "\n{s}"))
+            }
+            (None, None) => write!(f, "unknown error"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A problem with a custom mode that doesn't prevent it from being parsed,
+/// like a color that had to be approximated.
+#[derive(Debug)]
+pub struct Warning(Error);
+
+impl Warning {
+    pub(crate) fn new<T: Display>(msg: T, span: Option<Range<usize>>) -> Self {
+        Self(Error::custom(msg, span))
+    }
+
+    /// Applies `f` to the warning as an error, so it can be located like one.
+    pub(crate) fn map(self, f: impl FnOnce(Error) -> Error) -> Self {
+        Self(f(self.0))
+    }
+
+    /// The span of the warning within the file it occurred in.
+    ///
+    /// See [`Error::span`].
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.0.span()
+    }
+
+    /// Shows the warning, along with the part of the input it refers to.
+    ///
+    /// See [`Error::show`].
+    pub fn show<W: Write>(
+        &self,
+        writer: &mut AnsiWriter<W>,
+        input: Input<'_>,
+    ) -> io::Result<()> {
+        show(&self.0, writer, self.0.input(input), Severity::Warning)
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Whether a message shown by [`show`] is an error or a warning.
+#[derive(Clone, Copy)]
+enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+
+    /// The format used for the part of the input the message refers to.
+    fn highlight(self) -> &'static str {
+        match self {
+            Self::Error => "1;31",
+            Self::Warning => "1;33",
         }
     }
 }
@@ -247,10 +390,15 @@ fn show<W: Write>(
     error: &Error,
     writer: &mut AnsiWriter<W>,
     input: Input<'_>,
+    severity: Severity,
 ) -> io::Result<()> {
     let path = input.path;
     let text = input.text;
     let Some(span) = error.span().and_then(|mut span| {
+        // A span that doesn't refer to `text` can't be shown.
+        if !text.is_char_boundary(span.start) {
+            return None;
+        }
         // Make sure the span ends on a char boundary.
         span.end = (span.end..text.len())
             .find(|&i| text.is_char_boundary(i))
@@ -271,7 +419,7 @@ fn show<W: Write>(
         }
         Some(span)
     }) else {
-        writeln!(writer, "error in {}:", path.display())?;
+        writeln!(writer, "{} in {}:", severity.label(), path.display())?;
         return writeln!(writer.with_fmt("1"), "{error}");
     };
 
@@ -290,7 +438,8 @@ fn show<W: Write>(
 
     writeln!(
         writer,
-        "error in {}, line {start_lineno}, column {start_col}:",
+        "{} in {}, line {start_lineno}, column {start_col}:",
+        severity.label(),
         path.display(),
     )?;
     writeln!(writer.with_fmt("1"), "{error}")?;
@@ -323,7 +472,7 @@ fn show<W: Write>(
         if state == State::BeforeSpan {
             if next_i > start {
                 state = State::InSpan;
-                wf = writer.with_fmt("1;31");
+                wf = writer.with_fmt(severity.highlight());
             } else {
                 num_space += write_char_sized(c, &mut wf)?;
                 continue;
@@ -346,7 +495,7 @@ fn show<W: Write>(
     }
     (0..num_space).try_for_each(|_| write!(writer, " "))?;
     if num_caret > 0 {
-        let mut wf = writer.with_fmt("1;31");
+        let mut wf = writer.with_fmt(severity.highlight());
         write!(wf, "^")?;
         (1..num_caret).try_for_each(|_| write!(wf, "~"))?;
     }
@@ -361,7 +510,7 @@ fn show<W: Write>(
             let i = start_line_i + start_line.len() + 1;
             let line = first_line(&text[i..]);
             write!(writer, "{lineno:>width$} | ")?;
-            writeln!(writer.with_fmt("1;31"), "{line}")?;
+            writeln!(writer.with_fmt(severity.highlight()), "{line}")?;
         }
         n => {
             writeln!(writer, "{:>width$} | ({} lines omitted)", "...", n - 1)?;
@@ -375,7 +524,7 @@ fn show<W: Write>(
     // Index of the character directly after the last printed character.
     let mut next_i = end_line_i;
     let mut state = State::InSpan;
-    let mut wf = writer.with_fmt("1;31");
+    let mut wf = writer.with_fmt(severity.highlight());
     for (i, c) in end_line.char_indices() {
         let abs_i = end_line_i + i;
         next_i = abs_i + c.len_utf8();
@@ -395,7 +544,7 @@ fn show<W: Write>(
         num_caret += 1;
     }
     if num_caret > 0 {
-        let mut wf = writer.with_fmt("1;31");
+        let mut wf = writer.with_fmt(severity.highlight());
         (0..num_caret).try_for_each(|_| write!(wf, "~"))?;
     }
     writeln!(writer)?;
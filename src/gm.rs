@@ -0,0 +1,288 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Names from the General MIDI standard: percussion, instruments (programs),
+//! and controllers.
+
+/// The General MIDI percussion names, starting with note 35.
+const DRUMS: [&str; 47] = [
+    "acoustic-bass-drum",
+    "bass-drum-1",
+    "side-stick",
+    "acoustic-snare",
+    "hand-clap",
+    "electric-snare",
+    "low-floor-tom",
+    "closed-hi-hat",
+    "high-floor-tom",
+    "pedal-hi-hat",
+    "low-tom",
+    "open-hi-hat",
+    "low-mid-tom",
+    "hi-mid-tom",
+    "crash-cymbal-1",
+    "high-tom",
+    "ride-cymbal-1",
+    "chinese-cymbal",
+    "ride-bell",
+    "tambourine",
+    "splash-cymbal",
+    "cowbell",
+    "crash-cymbal-2",
+    "vibraslap",
+    "ride-cymbal-2",
+    "hi-bongo",
+    "low-bongo",
+    "mute-hi-conga",
+    "open-hi-conga",
+    "low-conga",
+    "high-timbale",
+    "low-timbale",
+    "high-agogo",
+    "low-agogo",
+    "cabasa",
+    "maracas",
+    "short-whistle",
+    "long-whistle",
+    "short-guiro",
+    "long-guiro",
+    "claves",
+    "hi-wood-block",
+    "low-wood-block",
+    "mute-cuica",
+    "open-cuica",
+    "mute-triangle",
+    "open-triangle",
+];
+
+/// The note of the first name in [`DRUMS`].
+const FIRST_DRUM: u8 = 35;
+
+/// Gets the note played by the General MIDI percussion instrument `name`,
+/// like `acoustic-snare`.
+pub fn drum(name: &str) -> Option<u8> {
+    let i = DRUMS.iter().position(|&d| d == name)?;
+    Some(FIRST_DRUM + i as u8)
+}
+
+/// The General MIDI instrument names, in order of program number (starting
+/// at 0).
+const PROGRAMS: [&str; 128] = [
+    "acoustic-grand-piano",
+    "bright-acoustic-piano",
+    "electric-grand-piano",
+    "honky-tonk-piano",
+    "electric-piano-1",
+    "electric-piano-2",
+    "harpsichord",
+    "clavinet",
+    "celesta",
+    "glockenspiel",
+    "music-box",
+    "vibraphone",
+    "marimba",
+    "xylophone",
+    "tubular-bells",
+    "dulcimer",
+    "drawbar-organ",
+    "percussive-organ",
+    "rock-organ",
+    "church-organ",
+    "reed-organ",
+    "accordion",
+    "harmonica",
+    "tango-accordion",
+    "acoustic-guitar-nylon",
+    "acoustic-guitar-steel",
+    "electric-guitar-jazz",
+    "electric-guitar-clean",
+    "electric-guitar-muted",
+    "overdriven-guitar",
+    "distortion-guitar",
+    "guitar-harmonics",
+    "acoustic-bass",
+    "electric-bass-finger",
+    "electric-bass-pick",
+    "fretless-bass",
+    "slap-bass-1",
+    "slap-bass-2",
+    "synth-bass-1",
+    "synth-bass-2",
+    "violin",
+    "viola",
+    "cello",
+    "contrabass",
+    "tremolo-strings",
+    "pizzicato-strings",
+    "orchestral-harp",
+    "timpani",
+    "string-ensemble-1",
+    "string-ensemble-2",
+    "synth-strings-1",
+    "synth-strings-2",
+    "choir-aahs",
+    "voice-oohs",
+    "synth-voice",
+    "orchestra-hit",
+    "trumpet",
+    "trombone",
+    "tuba",
+    "muted-trumpet",
+    "french-horn",
+    "brass-section",
+    "synth-brass-1",
+    "synth-brass-2",
+    "soprano-sax",
+    "alto-sax",
+    "tenor-sax",
+    "baritone-sax",
+    "oboe",
+    "english-horn",
+    "bassoon",
+    "clarinet",
+    "piccolo",
+    "flute",
+    "recorder",
+    "pan-flute",
+    "blown-bottle",
+    "shakuhachi",
+    "whistle",
+    "ocarina",
+    "lead-1-square",
+    "lead-2-sawtooth",
+    "lead-3-calliope",
+    "lead-4-chiff",
+    "lead-5-charang",
+    "lead-6-voice",
+    "lead-7-fifths",
+    "lead-8-bass-and-lead",
+    "pad-1-new-age",
+    "pad-2-warm",
+    "pad-3-polysynth",
+    "pad-4-choir",
+    "pad-5-bowed",
+    "pad-6-metallic",
+    "pad-7-halo",
+    "pad-8-sweep",
+    "fx-1-rain",
+    "fx-2-soundtrack",
+    "fx-3-crystal",
+    "fx-4-atmosphere",
+    "fx-5-brightness",
+    "fx-6-goblins",
+    "fx-7-echoes",
+    "fx-8-sci-fi",
+    "sitar",
+    "banjo",
+    "shamisen",
+    "koto",
+    "kalimba",
+    "bagpipe",
+    "fiddle",
+    "shanai",
+    "tinkle-bell",
+    "agogo",
+    "steel-drums",
+    "woodblock",
+    "taiko-drum",
+    "melodic-tom",
+    "synth-drum",
+    "reverse-cymbal",
+    "guitar-fret-noise",
+    "breath-noise",
+    "seashore",
+    "bird-tweet",
+    "telephone-ring",
+    "helicopter",
+    "applause",
+    "gunshot",
+];
+
+/// Gets the program number (starting at 0) of the General MIDI instrument
+/// `name`, like `electric-piano-1`.
+pub fn program(name: &str) -> Option<u8> {
+    PROGRAMS.iter().position(|&p| p == name).map(|i| i as u8)
+}
+
+/// The names of the General MIDI instruments.
+pub fn programs() -> impl Iterator<Item = &'static str> {
+    PROGRAMS.into_iter()
+}
+
+/// Names for the controllers defined by the MIDI standard, and their
+/// numbers.
+const CONTROLLERS: [(&str, u8); 47] = [
+    ("bank-select", 0),
+    ("modulation", 1),
+    ("breath", 2),
+    ("foot", 4),
+    ("portamento-time", 5),
+    ("data-entry", 6),
+    ("volume", 7),
+    ("balance", 8),
+    ("pan", 10),
+    ("expression", 11),
+    ("effect-1", 12),
+    ("effect-2", 13),
+    ("general-purpose-1", 16),
+    ("general-purpose-2", 17),
+    ("general-purpose-3", 18),
+    ("general-purpose-4", 19),
+    ("sustain", 64),
+    ("portamento", 65),
+    ("sostenuto", 66),
+    ("soft-pedal", 67),
+    ("legato", 68),
+    ("hold-2", 69),
+    ("sound-variation", 70),
+    ("resonance", 71),
+    ("release-time", 72),
+    ("attack-time", 73),
+    ("brightness", 74),
+    ("decay-time", 75),
+    ("vibrato-rate", 76),
+    ("vibrato-depth", 77),
+    ("vibrato-delay", 78),
+    ("portamento-control", 84),
+    ("reverb", 91),
+    ("tremolo", 92),
+    ("chorus", 93),
+    ("detune", 94),
+    ("phaser", 95),
+    ("data-increment", 96),
+    ("data-decrement", 97),
+    ("nrpn-lsb", 98),
+    ("nrpn-msb", 99),
+    ("rpn-lsb", 100),
+    ("rpn-msb", 101),
+    ("all-sound-off", 120),
+    ("reset-all-controllers", 121),
+    ("local-control", 122),
+    ("all-notes-off", 123),
+];
+
+/// Gets the number of the controller `name`, like `modulation`.
+pub fn controller(name: &str) -> Option<u8> {
+    CONTROLLERS.iter().find(|(c, _)| *c == name).map(|(_, n)| *n)
+}
+
+/// The names of the controllers.
+pub fn controllers() -> impl Iterator<Item = &'static str> {
+    CONTROLLERS.into_iter().map(|(name, _)| name)
+}
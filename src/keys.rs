@@ -0,0 +1,197 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Names of keys for keypresses, as listed in `doc/keycodes.md`.
+
+/// Key names and their USB HID keycodes. Where several keycodes share a
+/// name, only the first is listed.
+const KEYS: [(&str, u8); 162] = [
+    ("A", 0x04),
+    ("B", 0x05),
+    ("C", 0x06),
+    ("D", 0x07),
+    ("E", 0x08),
+    ("F", 0x09),
+    ("G", 0x0a),
+    ("H", 0x0b),
+    ("I", 0x0c),
+    ("J", 0x0d),
+    ("K", 0x0e),
+    ("L", 0x0f),
+    ("M", 0x10),
+    ("N", 0x11),
+    ("O", 0x12),
+    ("P", 0x13),
+    ("Q", 0x14),
+    ("R", 0x15),
+    ("S", 0x16),
+    ("T", 0x17),
+    ("U", 0x18),
+    ("V", 0x19),
+    ("W", 0x1a),
+    ("X", 0x1b),
+    ("Y", 0x1c),
+    ("Z", 0x1d),
+    ("1", 0x1e),
+    ("2", 0x1f),
+    ("3", 0x20),
+    ("4", 0x21),
+    ("5", 0x22),
+    ("6", 0x23),
+    ("7", 0x24),
+    ("8", 0x25),
+    ("9", 0x26),
+    ("0", 0x27),
+    ("Enter", 0x28),
+    ("Esc", 0x29),
+    ("Backspace", 0x2a),
+    ("Tab", 0x2b),
+    ("Space", 0x2c),
+    ("Minus", 0x2d),
+    ("Equal", 0x2e),
+    ("LeftBrace", 0x2f),
+    ("RightBrace", 0x30),
+    ("BackSlash", 0x31),
+    ("Semicolon", 0x33),
+    ("Apostrophe", 0x34),
+    ("Grave", 0x35),
+    ("Comma", 0x36),
+    ("Dot", 0x37),
+    ("Slash", 0x38),
+    ("CapsLock", 0x39),
+    ("F1", 0x3a),
+    ("F2", 0x3b),
+    ("F3", 0x3c),
+    ("F4", 0x3d),
+    ("F5", 0x3e),
+    ("F6", 0x3f),
+    ("F7", 0x40),
+    ("F8", 0x41),
+    ("F9", 0x42),
+    ("F10", 0x43),
+    ("F11", 0x44),
+    ("F12", 0x45),
+    ("SysRq", 0x46),
+    ("ScrollLock", 0x47),
+    ("Pause", 0x48),
+    ("Insert", 0x49),
+    ("Home", 0x4a),
+    ("PageUp", 0x4b),
+    ("Delete", 0x4c),
+    ("End", 0x4d),
+    ("PageDown", 0x4e),
+    ("Right", 0x4f),
+    ("Left", 0x50),
+    ("Down", 0x51),
+    ("Up", 0x52),
+    ("NumLock", 0x53),
+    ("KPSlash", 0x54),
+    ("KPAsterisk", 0x55),
+    ("KPMinus", 0x56),
+    ("KPPlus", 0x57),
+    ("KPEnter", 0x58),
+    ("KP1", 0x59),
+    ("KP2", 0x5a),
+    ("KP3", 0x5b),
+    ("KP4", 0x5c),
+    ("KP5", 0x5d),
+    ("KP6", 0x5e),
+    ("KP7", 0x5f),
+    ("KP8", 0x60),
+    ("KP9", 0x61),
+    ("KP0", 0x62),
+    ("KPDot", 0x63),
+    ("102nd", 0x64),
+    ("Compose", 0x65),
+    ("Power", 0x66),
+    ("KPEqual", 0x67),
+    ("F13", 0x68),
+    ("F14", 0x69),
+    ("F15", 0x6a),
+    ("F16", 0x6b),
+    ("F17", 0x6c),
+    ("F18", 0x6d),
+    ("F19", 0x6e),
+    ("F20", 0x6f),
+    ("F21", 0x70),
+    ("F22", 0x71),
+    ("F23", 0x72),
+    ("F24", 0x73),
+    ("Open", 0x74),
+    ("Help", 0x75),
+    ("Props", 0x76),
+    ("Front", 0x77),
+    ("Stop", 0x78),
+    ("Again", 0x79),
+    ("Undo", 0x7a),
+    ("Cut", 0x7b),
+    ("Copy", 0x7c),
+    ("Paste", 0x7d),
+    ("Find", 0x7e),
+    ("Mute", 0x7f),
+    ("VolumeUp", 0x80),
+    ("VolumeDown", 0x81),
+    ("KPComma", 0x85),
+    ("RO", 0x87),
+    ("Katakana/Hiragana", 0x88),
+    ("Yen", 0x89),
+    ("Henkan", 0x8a),
+    ("Muhenkan", 0x8b),
+    ("KPJpComma", 0x8c),
+    ("Hangeul", 0x90),
+    ("Hanja", 0x91),
+    ("Katakana", 0x92),
+    ("HIRAGANA", 0x93),
+    ("Zenkaku/Hankaku", 0x94),
+    ("KPLeftParenthesis", 0xb6),
+    ("KPRightParenthesis", 0xb7),
+    ("LeftControl", 0xe0),
+    ("LeftShift", 0xe1),
+    ("LeftAlt", 0xe2),
+    ("LeftMeta", 0xe3),
+    ("RightCtrl", 0xe4),
+    ("RightShift", 0xe5),
+    ("RightAlt", 0xe6),
+    ("RightMeta", 0xe7),
+    ("PlayPause", 0xe8),
+    ("StopCD", 0xe9),
+    ("PreviousSong", 0xea),
+    ("NextSong", 0xeb),
+    ("EjectCD", 0xec),
+    ("WWW", 0xf0),
+    ("Back", 0xf1),
+    ("Forward", 0xf2),
+    ("ScrollUp", 0xf5),
+    ("ScrollDown", 0xf6),
+    ("Edit", 0xf7),
+    ("Sleep", 0xf8),
+    ("Coffee", 0xf9),
+    ("Refresh", 0xfa),
+    ("Calc", 0xfb),
+];
+
+/// Gets the keycode of the key `name`, like `Enter` or `F5`, ignoring case.
+pub fn key(name: &str) -> Option<u8> {
+    KEYS.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, c)| *c)
+}
+
+/// The names of the keys.
+pub fn keys() -> impl Iterator<Item = &'static str> {
+    KEYS.into_iter().map(|(name, _)| name)
+}
@@ -17,16 +17,22 @@
  * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::common::{MidiValue, Name, VelocityCfg};
+use crate::common::{Color, MidiValue, ModeCfg, Name, VelocityCfg};
+use crate::controls::defaults::{self, Defaults, DefaultsCfg};
 use crate::controls::pad::{Pad, PadAction, PadCfg};
 use crate::controls::pot::{PickupCfg, Pot, PotCfg};
 use crate::controls::{self, Control, Optional, Pedal};
+use crate::decompile::{self, Reader};
+use crate::error::DeserializationError;
 use crate::parse::config::{ConfigSeed, DeserializeConfig};
 use crate::parse::{self, slice};
-use serde::Deserialize;
-use serde::de::{self, Deserializer};
-use std::fmt;
+use crate::ser::{Indexed, IndexedOptional};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
 use std::io::{self, Write};
+use std::marker::PhantomData;
 
 const COMPILE_CONFIG: controls::CompileCfg = controls::CompileCfgRequired {
     null_channel: 1,
@@ -42,6 +48,69 @@ where
     writer.write_all(b"\x05\x00\x45")
 }
 
+fn read_header(
+    reader: &mut Reader<'_>,
+    device_id: u8,
+) -> Result<(), decompile::Error> {
+    reader.expect(b"\xf0\x00\x20\x29\x02")?;
+    reader.expect(&[device_id])?;
+    reader.expect(b"\x05\x00\x45")
+}
+
+/// The configuration for deserializing one of a device's maps: the device's
+/// configuration for that kind of map (`C`), and the custom mode's.
+pub struct MapCfg<'a, C> {
+    pub map: &'a C,
+    pub mode: ModeCfg<'a>,
+    /// Whether the map is a section of a combined custom mode, whose controls
+    /// are keyed directly in the section, like `[pads.1]`.
+    pub section: bool,
+    /// For a section, the model given at the top level of the custom mode.
+    pub model: Option<Model>,
+}
+
+/// The configuration for deserializing a section of a combined custom mode.
+#[derive(Clone, Copy)]
+pub struct SectionCfg<'a> {
+    pub mode: ModeCfg<'a>,
+    /// The model given at the top level of the custom mode, which applies to
+    /// every section.
+    pub model: Option<Model>,
+}
+
+/// Serializes a map as a section of a combined custom mode, with its controls
+/// keyed directly in the section and without a `model` key.
+pub struct Section<'a, T>(pub &'a T);
+
+/// Reads the trailing list of addresses and the end of the SysEx message.
+fn read_footer<I>(
+    reader: &mut Reader<'_>,
+    addrs: I,
+) -> Result<(), decompile::Error>
+where
+    I: IntoIterator<Item = u8>,
+{
+    addrs.into_iter().try_for_each(|a| reader.expect(&[0x60, a]))?;
+    reader.expect(b"\xf7")
+}
+
+/// Returns the map type of the next custom mode in `reader` without
+/// consuming it, or [`None`] if there is no valid header.
+pub fn peek_map_type(reader: &Reader<'_>, device_id: u8) -> Option<u8> {
+    let mut reader = reader.clone();
+    read_header(&mut reader, device_id).ok()?;
+    reader.peek()
+}
+
+/// The error for a combined map without any of its sections, which are
+/// named by `sections`.
+pub fn no_sections<E: de::Error>(sections: &[&str]) -> E {
+    E::custom(format_args!(
+        "no maps defined; expected at least {}",
+        parse::one_of(sections.iter().copied()),
+    ))
+}
+
 #[derive(Clone, Copy, Debug)]
 struct StandardParams {
     pub map_type: u8,
@@ -84,10 +153,282 @@ impl StandardParams {
         ])?;
         self.name.compile(writer)
     }
+
+    pub fn read(
+        reader: &mut Reader<'_>,
+        map_type: u8,
+    ) -> Result<Self, decompile::Error> {
+        reader.expect(&[map_type, 0x7f, 0x00])?;
+        let active_color = reader.value()?.value();
+        reader.expect(&[0x04])?;
+        let any_notes = reader.byte()? == 0x42;
+        reader.expect(&[0x07])?;
+        reader.byte()?;
+        reader.expect(&[0x20])?;
+        Ok(Self {
+            map_type,
+            active_color,
+            any_notes,
+            name: reader.name()?,
+        })
+    }
+}
+
+/// A model of a keyboard, identified by its number of keys. Different models
+/// of the same device may have different controls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Model {
+    Keys25,
+    Keys37,
+    Keys49,
+    Keys61,
+    /// The Launchkey 88 \[MK3]. No other supported device has an 88-key
+    /// model.
+    Keys88,
+}
+
+impl Model {
+    const ALL: [Self; 5] =
+        [Self::Keys25, Self::Keys37, Self::Keys49, Self::Keys61, Self::Keys88];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Keys25 => "25",
+            Self::Keys37 => "37",
+            Self::Keys49 => "49",
+            Self::Keys61 => "61",
+            Self::Keys88 => "88",
+        }
+    }
+
+    /// Whether this model has faders and fader buttons.
+    pub fn has_faders(self) -> bool {
+        matches!(self, Self::Keys49 | Self::Keys61 | Self::Keys88)
+    }
+
+    /// Checks that this model is allowed by `config`, producing the same
+    /// errors as deserialization.
+    fn check<E: de::Error>(self, config: &ModelCfg) -> Result<(), E> {
+        #[derive(Clone, Copy)]
+        struct NoFaders(Model);
+
+        impl Display for NoFaders {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "the {}-key model has no faders", self.0.as_str())
+            }
+        }
+
+        if !config.models.contains(&self) {
+            Err(E::invalid_value(
+                de::Unexpected::Str(self.as_str()),
+                &ModelVisitor {
+                    cfg: config,
+                },
+            ))
+        } else if config.faders && !self.has_faders() {
+            Err(E::custom(NoFaders(self)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Serialize for Model {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ModelCfg {
+    /// The models of the device.
+    models: &'static [Model],
+    /// Whether the model must have faders.
+    faders: bool,
+}
+
+impl ModelCfg {
+    /// Allows any of `models`, whether or not it has faders.
+    pub const fn new(models: &'static [Model]) -> Self {
+        Self {
+            models,
+            faders: false,
+        }
+    }
+}
+
+struct ModelVisitor<'a> {
+    cfg: &'a ModelCfg,
+}
+
+impl de::Visitor<'_> for ModelVisitor<'_> {
+    type Value = Model;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let models = self.cfg.models.iter().map(|m| m.as_str());
+        write!(f, "{}", parse::one_of(models))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let model = Model::ALL
+            .into_iter()
+            .find(|m| m.as_str() == v)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))?;
+        model.check(self.cfg)?;
+        Ok(model)
+    }
+}
+
+impl<'a> DeserializeConfig<'a, ModelCfg> for Model {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModelCfg,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(ModelVisitor {
+            cfg: config,
+        })
+    }
+}
+
+/// Deserializes a map key of type `F`, which includes `model`, rejecting
+/// `model` if the device has only one model. `fields` are the allowed keys
+/// in that case.
+pub struct FieldSeed<F> {
+    models: &'static [Model],
+    fields: &'static [&'static str],
+    phantom: PhantomData<fn() -> F>,
+}
+
+impl<F> FieldSeed<F> {
+    pub fn new(
+        models: &'static [Model],
+        fields: &'static [&'static str],
+    ) -> Self {
+        Self {
+            models,
+            fields,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, F: DeserializeOwned> de::Visitor<'a> for FieldSeed<F> {
+    type Value = F;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v == "model" {
+            check_model_key(self.models, self.fields)?;
+        }
+        F::deserialize(de::value::StrDeserializer::new(v))
+    }
+}
+
+impl<'a, F: DeserializeOwned> DeserializeSeed<'a> for FieldSeed<F> {
+    type Value = F;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+/// A key of a map: either a field, or, in a section of a combined custom mode,
+/// the 0-based index of a control.
+enum Key<F> {
+    Field(F),
+    Item(usize),
+}
+
+/// Deserializes a [`Key`].
+struct KeySeed<F> {
+    field: FieldSeed<F>,
+    /// In a section, the number of controls keyed directly in it.
+    items: Option<usize>,
+}
+
+impl<F> KeySeed<F> {
+    /// Deserializes keys as [`FieldSeed::new`] does.
+    fn new(models: &'static [Model], fields: &'static [&'static str]) -> Self {
+        Self {
+            field: FieldSeed::new(models, fields),
+            items: None,
+        }
+    }
+
+    /// Deserializes the keys of a section, which are `fields` (without
+    /// `model`, which is at the top level) and the keys of `len` controls.
+    fn section(mut self, fields: &'static [&'static str], len: usize) -> Self {
+        self.field = FieldSeed::new(&[], fields);
+        self.items = Some(len);
+        self
+    }
+}
+
+impl<'a, F: DeserializeOwned> de::Visitor<'a> for KeySeed<F> {
+    type Value = Key<F>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if let Some(len) = self.items {
+            if v != "model" && !self.field.fields.contains(&v) {
+                return slice::item_index(v, len).map(Key::Item);
+            }
+        }
+        self.field.visit_str(v).map(Key::Field)
+    }
+}
+
+impl<'a, F: DeserializeOwned> DeserializeSeed<'a> for KeySeed<F> {
+    type Value = Key<F>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+/// Returns an error if a device with `models` can't have a `model` key,
+/// because it has only one model. `fields` are the allowed keys.
+fn check_model_key<E: de::Error>(
+    models: &[Model],
+    fields: &'static [&'static str],
+) -> Result<(), E> {
+    if models.is_empty() {
+        Err(E::unknown_field("model", fields))
+    } else {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct PadMap {
+    model: Option<Model>,
     name: Name,
     active_color: MidiValue,
     pads: [Optional<Pad>; Self::NUM_PADS],
@@ -95,6 +436,11 @@ pub struct PadMap {
 
 impl PadMap {
     pub const NUM_PADS: usize = 16;
+    pub const MAP_TYPE: u8 = 0x01;
+
+    pub fn model(&self) -> Option<Model> {
+        self.model
+    }
 
     pub fn compile<W>(&self, device_id: u8, writer: &mut W) -> io::Result<()>
     where
@@ -102,7 +448,7 @@ impl PadMap {
     {
         write_header(writer, device_id)?;
         StandardParams {
-            map_type: 0x01,
+            map_type: Self::MAP_TYPE,
             active_color: self.active_color.value(),
             any_notes: any_notes(&self.pads),
             name: self.name,
@@ -116,17 +462,96 @@ impl PadMap {
         }
         writer.write_all(b"\xf7")
     }
+
+    pub fn decompile(
+        device_id: u8,
+        reader: &mut Reader<'_>,
+    ) -> Result<Self, decompile::Error> {
+        read_header(reader, device_id)?;
+        let params = StandardParams::read(reader, Self::MAP_TYPE)?;
+        let mut pads = [Optional::None; Self::NUM_PADS];
+        for (i, pad) in pads.iter_mut().enumerate() {
+            *pad = Control::decompile(i as u8, reader, &COMPILE_CONFIG)?;
+        }
+        read_footer(reader, 0..Self::NUM_PADS as u8)?;
+        Ok(Self {
+            model: None,
+            name: params.name,
+            active_color: MidiValue::new(params.active_color).unwrap(),
+            pads,
+        })
+    }
+}
+
+impl Serialize for PadMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            model: Option<Model>,
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            active_color: MidiValue,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            pads: IndexedOptional<'a, Pad>,
+        }
+
+        Fields {
+            model: self.model,
+            name: self.name,
+            active_color: self.active_color,
+            pads: IndexedOptional(&self.pads),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Section<'_, PadMap> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            active_color: MidiValue,
+            #[serde(flatten)]
+            pads: IndexedOptional<'a, Pad>,
+        }
+
+        Fields {
+            name: self.0.name,
+            active_color: self.0.active_color,
+            pads: IndexedOptional(&self.0.pads),
+        }
+        .serialize(serializer)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct PadMapCfg {
     keypress: bool,
+    models: &'static [Model],
 }
 
 impl PadMapCfg {
+    /// The fields of a [`PadMap`] when the device has only one model.
+    const FIELDS: &'static [&'static str] =
+        &["name", "active-color", "defaults", "pads"];
+    /// The fields of a [`PadMap`] in a section of a combined custom mode.
+    const SECTION_FIELDS: &'static [&'static str] =
+        &["name", "active-color", "defaults"];
+
     pub const fn new() -> Self {
         Self {
             keypress: false,
+            models: &[],
         }
     }
 
@@ -134,12 +559,26 @@ impl PadMapCfg {
         self.keypress = allowed;
         self
     }
+
+    /// Sets the models of the device, which may be selected with a `model`
+    /// key. If empty (the default), the device has only one model.
+    pub const fn models(mut self, models: &'static [Model]) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn model_cfg(&self) -> ModelCfg {
+        ModelCfg {
+            models: self.models,
+            faders: false,
+        }
+    }
 }
 
-impl<'a> DeserializeConfig<'a, PadMapCfg> for PadMap {
+impl<'a> DeserializeConfig<'a, MapCfg<'_, PadMapCfg>> for PadMap {
     fn deserialize<D>(
         deserializer: D,
-        config: &PadMapCfg,
+        config: &MapCfg<'_, PadMapCfg>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -148,13 +587,18 @@ impl<'a> DeserializeConfig<'a, PadMapCfg> for PadMap {
         #[serde(field_identifier, rename_all = "kebab-case")]
         #[serde(expecting = "key")]
         enum Field {
+            Model,
             Name,
             ActiveColor,
+            Defaults,
             Pads,
         }
 
         struct Visitor<'a> {
             cfg: &'a PadMapCfg,
+            mode: ModeCfg<'a>,
+            section: bool,
+            model: Option<Model>,
         }
 
         impl<'a> de::Visitor<'a> for Visitor<'_> {
@@ -168,23 +612,77 @@ impl<'a> DeserializeConfig<'a, PadMapCfg> for PadMap {
             where
                 A: de::MapAccess<'a>,
             {
+                let pad_cfg = PadCfg::new(VelocityCfg::Any, self.mode)
+                    .keypress(self.cfg.keypress);
+                let key = || {
+                    let key = KeySeed::new(self.cfg.models, PadMapCfg::FIELDS);
+                    if self.section {
+                        key.section(
+                            PadMapCfg::SECTION_FIELDS,
+                            PadMap::NUM_PADS,
+                        )
+                    } else {
+                        key
+                    }
+                };
+                let mut model = self.model;
                 let mut name = None;
                 let mut active_color = None;
+                let mut defaults: Option<Defaults> = None;
                 let mut pads = None;
-                while let Some(field) = map.next_key()? {
+                let mut items = None;
+                while let Some(key) = map.next_key_seed(key())? {
+                    let field = match key {
+                        Key::Field(field) => field,
+                        Key::Item(i) => {
+                            let pad = defaults.unwrap_or_default().pad;
+                            let cfg = pad_cfg.clone().defaults(pad);
+                            items
+                                .get_or_insert_with(|| {
+                                    slice::Inline::new(PadMap::NUM_PADS)
+                                })
+                                .next_value_seed(
+                                    &mut map,
+                                    i,
+                                    ConfigSeed::new(&cfg),
+                                )?;
+                            continue;
+                        }
+                    };
                     match field {
+                        Field::Model => {
+                            parse::check_dup(&model, "model")?;
+                            let cfg = self.cfg.model_cfg();
+                            model = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
+                        }
                         Field::Name => {
                             parse::check_dup(&name, "name")?;
                             name = Some(map.next_value()?);
                         }
                         Field::ActiveColor => {
                             parse::check_dup(&active_color, "active-color")?;
-                            active_color = Some(map.next_value()?);
+                            active_color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(&self.mode),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::Defaults => {
+                            parse::check_dup(&defaults, "defaults")?;
+                            defaults::check_order(&pads, "pads")?;
+                            defaults::check_order(&items, "pads")?;
+                            let cfg = DefaultsCfg::new().pad("pad", &pad_cfg);
+                            defaults = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
                         }
                         Field::Pads => {
                             parse::check_dup(&pads, "pads")?;
-                            let cfg = PadCfg::new(VelocityCfg::Any)
-                                .keypress(self.cfg.keypress);
+                            let pad = defaults.unwrap_or_default().pad;
+                            let cfg = pad_cfg.clone().defaults(pad);
                             let b = map.next_value_seed(slice::Seed::new(
                                 || ConfigSeed::new(&cfg),
                                 PadMap::NUM_PADS,
@@ -193,8 +691,13 @@ impl<'a> DeserializeConfig<'a, PadMapCfg> for PadMap {
                         }
                     }
                 }
+                if let Some(items) = items {
+                    let b = items.finish(|| ConfigSeed::new(&pad_cfg))?;
+                    pads = Some(*Box::try_from(b).unwrap());
+                }
                 let missing = de::Error::missing_field;
                 Ok(PadMap {
+                    model,
                     name: name.unwrap_or_else(Name::empty),
                     active_color: active_color
                         .ok_or_else(|| missing("active-color"))?,
@@ -204,52 +707,132 @@ impl<'a> DeserializeConfig<'a, PadMapCfg> for PadMap {
         }
 
         deserializer.deserialize_map(Visitor {
-            cfg: config,
+            cfg: config.map,
+            mode: config.mode,
+            section: config.section,
+            model: config.model,
         })
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct PotMap {
+    model: Option<Model>,
     name: Name,
     pots: [Pot; Self::NUM_POTS],
 }
 
 impl PotMap {
     pub const NUM_POTS: usize = 8;
+    pub const MAP_TYPE: u8 = 0x00;
+    const BASE_ADDR: u8 = 0x38;
+
+    pub fn model(&self) -> Option<Model> {
+        self.model
+    }
 
     pub fn compile<W>(&self, device_id: u8, writer: &mut W) -> io::Result<()>
     where
         W: Write,
     {
-        const BASE_ADDR: u8 = 0x38;
         write_header(writer, device_id)?;
         StandardParams {
-            map_type: 0x00,
+            map_type: Self::MAP_TYPE,
             active_color: 0x1a,
             any_notes: false,
             name: self.name,
         }
         .compile(writer)?;
         for (i, pot) in self.pots.iter().enumerate() {
-            pot.compile(BASE_ADDR + i as u8, writer, &COMPILE_CONFIG)?;
+            pot.compile(Self::BASE_ADDR + i as u8, writer, &COMPILE_CONFIG)?;
         }
         for (i, _) in self.pots.iter().enumerate() {
-            writer.write_all(&[0x60, BASE_ADDR + i as u8])?;
+            writer.write_all(&[0x60, Self::BASE_ADDR + i as u8])?;
         }
         writer.write_all(b"\xf7")
     }
+
+    pub fn decompile(
+        device_id: u8,
+        reader: &mut Reader<'_>,
+    ) -> Result<Self, decompile::Error> {
+        read_header(reader, device_id)?;
+        let params = StandardParams::read(reader, Self::MAP_TYPE)?;
+        let mut pots = Vec::with_capacity(Self::NUM_POTS);
+        for i in 0..Self::NUM_POTS as u8 {
+            let addr = Self::BASE_ADDR + i;
+            pots.push(Control::decompile(addr, reader, &COMPILE_CONFIG)?);
+        }
+        let addrs = 0..Self::NUM_POTS as u8;
+        read_footer(reader, addrs.map(|i| Self::BASE_ADDR + i))?;
+        Ok(Self {
+            model: None,
+            name: params.name,
+            pots: pots.try_into().unwrap(),
+        })
+    }
+}
+
+impl Serialize for PotMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            model: Option<Model>,
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            pots: Indexed<'a, Pot>,
+        }
+
+        Fields {
+            model: self.model,
+            name: self.name,
+            pots: Indexed(&self.pots),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Section<'_, PotMap> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            #[serde(flatten)]
+            pots: Indexed<'a, Pot>,
+        }
+
+        Fields {
+            name: self.0.name,
+            pots: Indexed(&self.0.pots),
+        }
+        .serialize(serializer)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct PotMapCfg {
     pickup: PickupCfg,
+    models: &'static [Model],
 }
 
 impl PotMapCfg {
+    /// The fields of a [`PotMap`] when the device has only one model.
+    const FIELDS: &'static [&'static str] = &["name", "defaults", "pots"];
+    /// The fields of a [`PotMap`] in a section of a combined custom mode.
+    const SECTION_FIELDS: &'static [&'static str] = &["name", "defaults"];
+
     pub const fn new() -> Self {
         Self {
             pickup: PickupCfg::BinaryOnly,
+            models: &[],
         }
     }
 
@@ -257,12 +840,25 @@ impl PotMapCfg {
         self.pickup = cfg;
         self
     }
+
+    /// Sets the models of the device; see [`PadMapCfg::models`].
+    pub const fn models(mut self, models: &'static [Model]) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn model_cfg(&self) -> ModelCfg {
+        ModelCfg {
+            models: self.models,
+            faders: false,
+        }
+    }
 }
 
-impl<'a> DeserializeConfig<'a, PotMapCfg> for PotMap {
+impl<'a> DeserializeConfig<'a, MapCfg<'_, PotMapCfg>> for PotMap {
     fn deserialize<D>(
         deserializer: D,
-        config: &PotMapCfg,
+        config: &MapCfg<'_, PotMapCfg>,
     ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
@@ -271,12 +867,17 @@ impl<'a> DeserializeConfig<'a, PotMapCfg> for PotMap {
         #[serde(field_identifier, rename_all = "kebab-case")]
         #[serde(expecting = "key")]
         enum Field {
+            Model,
             Name,
+            Defaults,
             Pots,
         }
 
         struct Visitor<'a> {
             cfg: &'a PotMapCfg,
+            mode: ModeCfg<'a>,
+            section: bool,
+            model: Option<Model>,
         }
 
         impl<'a> de::Visitor<'a> for Visitor<'_> {
@@ -290,17 +891,66 @@ impl<'a> DeserializeConfig<'a, PotMapCfg> for PotMap {
             where
                 A: de::MapAccess<'a>,
             {
+                let pot_cfg = PotCfg::new(self.mode).pickup(self.cfg.pickup);
+                let key = || {
+                    let key = KeySeed::new(self.cfg.models, PotMapCfg::FIELDS);
+                    if self.section {
+                        key.section(
+                            PotMapCfg::SECTION_FIELDS,
+                            PotMap::NUM_POTS,
+                        )
+                    } else {
+                        key
+                    }
+                };
+                let mut model = self.model;
                 let mut name = None;
+                let mut defaults: Option<Defaults> = None;
                 let mut pots = None;
-                while let Some(field) = map.next_key()? {
+                let mut items = None;
+                while let Some(key) = map.next_key_seed(key())? {
+                    let field = match key {
+                        Key::Field(field) => field,
+                        Key::Item(i) => {
+                            let pot = defaults.unwrap_or_default().pot;
+                            let cfg = pot_cfg.defaults(pot);
+                            items
+                                .get_or_insert_with(|| {
+                                    slice::Inline::new(PotMap::NUM_POTS)
+                                })
+                                .next_value_seed(
+                                    &mut map,
+                                    i,
+                                    ConfigSeed::new(&cfg),
+                                )?;
+                            continue;
+                        }
+                    };
                     match field {
+                        Field::Model => {
+                            parse::check_dup(&model, "model")?;
+                            let cfg = self.cfg.model_cfg();
+                            model = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
+                        }
                         Field::Name => {
                             parse::check_dup(&name, "name")?;
                             name = Some(map.next_value()?);
                         }
+                        Field::Defaults => {
+                            parse::check_dup(&defaults, "defaults")?;
+                            defaults::check_order(&pots, "pots")?;
+                            defaults::check_order(&items, "pots")?;
+                            let cfg = DefaultsCfg::new().pot("pot", &pot_cfg);
+                            defaults = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
+                        }
                         Field::Pots => {
                             parse::check_dup(&pots, "pots")?;
-                            let cfg = PotCfg::new().pickup(self.cfg.pickup);
+                            let pot = defaults.unwrap_or_default().pot;
+                            let cfg = pot_cfg.defaults(pot);
                             let b = map.next_value_seed(slice::Seed::new(
                                 || ConfigSeed::new(&cfg),
                                 PotMap::NUM_POTS,
@@ -309,8 +959,16 @@ impl<'a> DeserializeConfig<'a, PotMapCfg> for PotMap {
                         }
                     }
                 }
+                if self.section {
+                    let items = items.unwrap_or_else(|| {
+                        slice::Inline::new(PotMap::NUM_POTS)
+                    });
+                    let b = items.finish(|| ConfigSeed::new(&pot_cfg))?;
+                    pots = Some(*Box::try_from(b).unwrap());
+                }
                 let missing = de::Error::missing_field;
                 Ok(PotMap {
+                    model,
                     name: name.unwrap_or_else(Name::empty),
                     pots: pots.ok_or_else(|| missing("pots"))?,
                 })
@@ -318,33 +976,81 @@ impl<'a> DeserializeConfig<'a, PotMapCfg> for PotMap {
         }
 
         deserializer.deserialize_map(Visitor {
-            cfg: config,
+            cfg: config.map,
+            mode: config.mode,
+            section: config.section,
+            model: config.model,
         })
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize)]
 #[serde(transparent)]
 pub struct PedalMap {
     pedal: Pedal,
 }
 
+impl Serialize for Section<'_, PedalMap> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for PedalMap {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        Pedal::deserialize(deserializer, config.aliases).map(|pedal| Self {
+            pedal,
+        })
+    }
+}
+
 impl PedalMap {
+    pub const MAP_TYPE: u8 = 0x02;
+    const ADDR: u8 = 0x78;
+
+    /// Always [`None`], as a pedal map is the same for every model.
+    pub fn model(&self) -> Option<Model> {
+        None
+    }
+
     pub fn compile<W>(&self, device_id: u8, writer: &mut W) -> io::Result<()>
     where
         W: Write,
     {
-        const ADDR: u8 = 0x78;
         write_header(writer, device_id)?;
-        writer.write_all(&[0x02, 0x00, 0x00, 0x1a, 0x20])?;
+        writer.write_all(&[Self::MAP_TYPE, 0x00, 0x00, 0x1a, 0x20])?;
         Name::empty().compile(writer)?;
-        self.pedal.compile(ADDR, writer, &COMPILE_CONFIG)?;
+        self.pedal.compile(Self::ADDR, writer, &COMPILE_CONFIG)?;
         writer.write_all(&[0xf7])
     }
+
+    pub fn decompile(
+        device_id: u8,
+        reader: &mut Reader<'_>,
+    ) -> Result<Self, decompile::Error> {
+        read_header(reader, device_id)?;
+        reader.expect(&[Self::MAP_TYPE, 0x00, 0x00, 0x1a, 0x20])?;
+        reader.expect(&[0x00])?;
+        let pedal = Control::decompile(Self::ADDR, reader, &COMPILE_CONFIG)?;
+        reader.expect(&[0xf7])?;
+        Ok(Self {
+            pedal,
+        })
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct FaderMap {
+    model: Option<Model>,
     name: Name,
     active_color: MidiValue,
     faders: [Optional<Pot>; Self::NUM_FADERS],
@@ -354,39 +1060,223 @@ pub struct FaderMap {
 impl FaderMap {
     pub const NUM_FADERS: usize = 9;
     pub const NUM_BUTTONS: usize = 9;
+    pub const MAP_TYPE: u8 = 0x03;
+    const FADER_ADDR: u8 = 0x50;
+    const BUTTON_ADDR: u8 = 0x28;
+
+    /// The fixed color of the last button.
+    const LAST_BUTTON_COLOR: MidiValue = match MidiValue::new(2) {
+        Some(v) => v,
+        None => unreachable!(),
+    };
+
+    pub fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    fn fader_cfg(mode: ModeCfg<'_>) -> PotCfg<'_> {
+        PotCfg::new(mode).pickup(PickupCfg::GlobalAllowed).name("fader")
+    }
+
+    fn button_cfg(index: usize, mode: ModeCfg<'_>) -> PadCfg<'_> {
+        let cfg = PadCfg::new(VelocityCfg::FixedOnly, mode)
+            .keypress(true)
+            .name("button");
+        if index == Self::NUM_BUTTONS - 1 {
+            cfg.color(Self::LAST_BUTTON_COLOR)
+        } else {
+            cfg
+        }
+    }
 
     pub fn compile<W>(&self, device_id: u8, writer: &mut W) -> io::Result<()>
     where
         W: Write,
     {
-        const FADER_ADDR: u8 = 0x50;
-        const BUTTON_ADDR: u8 = 0x28;
         write_header(writer, device_id)?;
         StandardParams {
-            map_type: 0x03,
+            map_type: Self::MAP_TYPE,
             active_color: self.active_color.value(),
             any_notes: any_notes(&self.buttons),
             name: self.name,
         }
         .compile(writer)?;
         for (i, fader) in self.faders.iter().enumerate() {
-            fader.compile(FADER_ADDR + i as u8, writer, &COMPILE_CONFIG)?;
+            fader.compile(
+                Self::FADER_ADDR + i as u8,
+                writer,
+                &COMPILE_CONFIG,
+            )?;
         }
         for (i, button) in self.buttons.iter().enumerate() {
-            button.compile(BUTTON_ADDR + i as u8, writer, &COMPILE_CONFIG)?;
+            button.compile(
+                Self::BUTTON_ADDR + i as u8,
+                writer,
+                &COMPILE_CONFIG,
+            )?;
         }
         for (i, _) in self.faders.iter().enumerate() {
-            writer.write_all(&[0x60, FADER_ADDR + i as u8])?;
+            writer.write_all(&[0x60, Self::FADER_ADDR + i as u8])?;
         }
         for (i, _) in self.buttons.iter().enumerate() {
-            writer.write_all(&[0x60, BUTTON_ADDR + i as u8])?;
+            writer.write_all(&[0x60, Self::BUTTON_ADDR + i as u8])?;
         }
         writer.write_all(b"\xf7")
     }
+
+    pub fn decompile(
+        device_id: u8,
+        reader: &mut Reader<'_>,
+    ) -> Result<Self, decompile::Error> {
+        read_header(reader, device_id)?;
+        let params = StandardParams::read(reader, Self::MAP_TYPE)?;
+        let mut faders = [Optional::None; Self::NUM_FADERS];
+        for (i, fader) in faders.iter_mut().enumerate() {
+            let addr = Self::FADER_ADDR + i as u8;
+            *fader = Control::decompile(addr, reader, &COMPILE_CONFIG)?;
+        }
+        let mut buttons = [Optional::None; Self::NUM_BUTTONS];
+        for (i, button) in buttons.iter_mut().enumerate() {
+            let addr = Self::BUTTON_ADDR + i as u8;
+            *button = Control::decompile(addr, reader, &COMPILE_CONFIG)?;
+        }
+        let fader_addrs =
+            (0..Self::NUM_FADERS as u8).map(|i| i + Self::FADER_ADDR);
+        let button_addrs =
+            (0..Self::NUM_BUTTONS as u8).map(|i| i + Self::BUTTON_ADDR);
+        read_footer(reader, fader_addrs.chain(button_addrs))?;
+        Ok(Self {
+            model: None,
+            name: params.name,
+            active_color: MidiValue::new(params.active_color).unwrap(),
+            faders,
+            buttons,
+        })
+    }
 }
 
-impl<'a> Deserialize<'a> for FaderMap {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+impl Serialize for FaderMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn serialize_buttons<S>(
+            buttons: &IndexedOptional<'_, Pad>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            buttons.serialize_with(serializer, |i| {
+                FaderMap::button_cfg(i, ModeCfg::NONE)
+            })
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            model: Option<Model>,
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            active_color: MidiValue,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            faders: IndexedOptional<'a, Pot>,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            #[serde(serialize_with = "serialize_buttons")]
+            buttons: IndexedOptional<'a, Pad>,
+        }
+
+        Fields {
+            model: self.model,
+            name: self.name,
+            active_color: self.active_color,
+            faders: IndexedOptional(&self.faders),
+            buttons: IndexedOptional(&self.buttons),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Section<'_, FaderMap> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn serialize_buttons<S>(
+            buttons: &IndexedOptional<'_, Pad>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            buttons.serialize_with(serializer, |i| {
+                FaderMap::button_cfg(i, ModeCfg::NONE)
+            })
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            active_color: MidiValue,
+            #[serde(flatten)]
+            faders: IndexedOptional<'a, Pot>,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            #[serde(serialize_with = "serialize_buttons")]
+            buttons: IndexedOptional<'a, Pad>,
+        }
+
+        Fields {
+            name: self.0.name,
+            active_color: self.0.active_color,
+            faders: IndexedOptional(&self.0.faders),
+            buttons: IndexedOptional(&self.0.buttons),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FaderMapCfg {
+    models: &'static [Model],
+}
+
+impl FaderMapCfg {
+    /// The fields of a [`FaderMap`] when the device has only one model.
+    const FIELDS: &'static [&'static str] =
+        &["name", "active-color", "defaults", "faders", "buttons"];
+    /// The fields of a [`FaderMap`] in a section of a combined custom mode.
+    const SECTION_FIELDS: &'static [&'static str] =
+        &["name", "active-color", "defaults", "buttons"];
+
+    pub const fn new() -> Self {
+        Self {
+            models: &[],
+        }
+    }
+
+    /// Sets the models of the device; see [`PadMapCfg::models`]. Models
+    /// without faders are rejected.
+    pub const fn models(mut self, models: &'static [Model]) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn model_cfg(&self) -> ModelCfg {
+        ModelCfg {
+            models: self.models,
+            faders: true,
+        }
+    }
+}
+
+impl<'a> DeserializeConfig<'a, MapCfg<'_, FaderMapCfg>> for FaderMap {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &MapCfg<'_, FaderMapCfg>,
+    ) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
@@ -394,19 +1284,21 @@ impl<'a> Deserialize<'a> for FaderMap {
         #[serde(field_identifier, rename_all = "kebab-case")]
         #[serde(expecting = "key")]
         enum Field {
+            Model,
             Name,
             ActiveColor,
+            Defaults,
             Faders,
             Buttons,
         }
 
-        struct ButtonElemSeed {
-            normal: PadCfg,
-            last: PadCfg,
+        struct ButtonElemSeed<'a> {
+            normal: PadCfg<'a>,
+            last: PadCfg<'a>,
         }
 
-        impl<'b> slice::ElementSeed<'_> for &'b ButtonElemSeed {
-            type Seed = ConfigSeed<'b, Optional<Pad>, PadCfg>;
+        impl<'b, 'c> slice::ElementSeed<'_> for &'b ButtonElemSeed<'c> {
+            type Seed = ConfigSeed<'b, Optional<Pad>, PadCfg<'c>>;
 
             fn get(&self, index: usize) -> Self::Seed {
                 ConfigSeed::new(if index == FaderMap::NUM_BUTTONS - 1 {
@@ -417,14 +1309,14 @@ impl<'a> Deserialize<'a> for FaderMap {
             }
         }
 
-        const LAST_BUTTON_COLOR: MidiValue = match MidiValue::new(2) {
-            Some(v) => v,
-            None => unreachable!(),
-        };
-
-        struct Visitor;
+        struct Visitor<'a> {
+            cfg: &'a FaderMapCfg,
+            mode: ModeCfg<'a>,
+            section: bool,
+            model: Option<Model>,
+        }
 
-        impl<'a> de::Visitor<'a> for Visitor {
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
             type Value = FaderMap;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -435,25 +1327,87 @@ impl<'a> Deserialize<'a> for FaderMap {
             where
                 A: de::MapAccess<'a>,
             {
+                let fader_cfg = FaderMap::fader_cfg(self.mode);
+                let button_cfg = FaderMap::button_cfg(0, self.mode);
+                let key = || {
+                    let key =
+                        KeySeed::new(self.cfg.models, FaderMapCfg::FIELDS);
+                    if self.section {
+                        key.section(
+                            FaderMapCfg::SECTION_FIELDS,
+                            FaderMap::NUM_FADERS,
+                        )
+                    } else {
+                        key
+                    }
+                };
+                // A model from the top level of a combined custom mode must
+                // also have faders.
+                if let Some(model) = self.model {
+                    model.check(&self.cfg.model_cfg())?;
+                }
+                let mut model = self.model;
                 let mut name = None;
                 let mut active_color = None;
+                let mut defaults: Option<Defaults> = None;
                 let mut faders = None;
                 let mut buttons = None;
-                while let Some(field) = map.next_key()? {
+                let mut items = None;
+                while let Some(key) = map.next_key_seed(key())? {
+                    let field = match key {
+                        Key::Field(field) => field,
+                        Key::Item(i) => {
+                            let pot = defaults.unwrap_or_default().pot;
+                            let cfg = fader_cfg.defaults(pot);
+                            items
+                                .get_or_insert_with(|| {
+                                    slice::Inline::new(FaderMap::NUM_FADERS)
+                                })
+                                .next_value_seed(
+                                    &mut map,
+                                    i,
+                                    ConfigSeed::new(&cfg),
+                                )?;
+                            continue;
+                        }
+                    };
                     match field {
+                        Field::Model => {
+                            parse::check_dup(&model, "model")?;
+                            let cfg = self.cfg.model_cfg();
+                            model = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
+                        }
                         Field::Name => {
                             parse::check_dup(&name, "name")?;
                             name = Some(map.next_value()?);
                         }
                         Field::ActiveColor => {
                             parse::check_dup(&active_color, "active-color")?;
-                            active_color = Some(map.next_value()?);
+                            active_color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(&self.mode),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::Defaults => {
+                            parse::check_dup(&defaults, "defaults")?;
+                            defaults::check_order(&faders, "faders")?;
+                            defaults::check_order(&items, "faders")?;
+                            defaults::check_order(&buttons, "buttons")?;
+                            let cfg = DefaultsCfg::new()
+                                .pot("fader", &fader_cfg)
+                                .pad("button", &button_cfg);
+                            defaults = Some(
+                                map.next_value_seed(ConfigSeed::new(&cfg))?,
+                            );
                         }
                         Field::Faders => {
                             parse::check_dup(&faders, "faders")?;
-                            let cfg = PotCfg::new()
-                                .pickup(PickupCfg::GlobalAllowed)
-                                .name("fader");
+                            let pot = defaults.unwrap_or_default().pot;
+                            let cfg = fader_cfg.defaults(pot);
                             let b = map.next_value_seed(slice::Seed::new(
                                 || ConfigSeed::new(&cfg),
                                 FaderMap::NUM_FADERS,
@@ -462,12 +1416,14 @@ impl<'a> Deserialize<'a> for FaderMap {
                         }
                         Field::Buttons => {
                             parse::check_dup(&buttons, "buttons")?;
-                            let cfg = PadCfg::new(VelocityCfg::FixedOnly)
-                                .keypress(true)
-                                .name("button");
+                            let pad = defaults.unwrap_or_default().pad;
                             let elem = ButtonElemSeed {
-                                normal: cfg.clone(),
-                                last: cfg.color(LAST_BUTTON_COLOR),
+                                normal: button_cfg.clone().defaults(pad),
+                                last: FaderMap::button_cfg(
+                                    FaderMap::NUM_BUTTONS - 1,
+                                    self.mode,
+                                )
+                                .defaults(pad),
                             };
                             let b = map.next_value_seed(slice::Seed::new(
                                 &elem,
@@ -477,8 +1433,13 @@ impl<'a> Deserialize<'a> for FaderMap {
                         }
                     }
                 }
+                if let Some(items) = items {
+                    let b = items.finish(|| ConfigSeed::new(&fader_cfg))?;
+                    faders = Some(*Box::try_from(b).unwrap());
+                }
                 let missing = de::Error::missing_field;
                 Ok(FaderMap {
+                    model,
                     name: name.unwrap_or_else(Name::empty),
                     active_color: active_color
                         .ok_or_else(|| missing("active-color"))?,
@@ -488,6 +1449,282 @@ impl<'a> Deserialize<'a> for FaderMap {
             }
         }
 
-        deserializer.deserialize_map(Visitor)
+        deserializer.deserialize_map(Visitor {
+            cfg: config.map,
+            mode: config.mode,
+            section: config.section,
+            model: config.model,
+        })
+    }
+}
+
+/// Builds a [`PadMap`] in code, running the same checks as deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct PadMapBuilder<M> {
+    cfg: &'static PadMapCfg,
+    model: Option<Model>,
+    name: Name,
+    active_color: Option<MidiValue>,
+    pads: [Optional<Pad>; PadMap::NUM_PADS],
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: From<PadMap>> PadMapBuilder<M> {
+    pub(super) fn new(cfg: &'static PadMapCfg) -> Self {
+        Self {
+            cfg,
+            model: None,
+            name: Name::empty(),
+            active_color: None,
+            pads: [Optional::None; PadMap::NUM_PADS],
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the model of the keyboard, so that [`Self::build`] fails if the
+    /// model doesn't have the controls in this map.
+    pub fn model(mut self, model: Model) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn name(mut self, name: Name) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn active_color(mut self, color: MidiValue) -> Self {
+        self.active_color = Some(color);
+        self
+    }
+
+    /// Sets pad `n` (starting at 1), failing if `n` is not between 1 and
+    /// [`PadMap::NUM_PADS`].
+    pub fn pad(
+        mut self,
+        n: usize,
+        pad: Pad,
+    ) -> Result<Self, DeserializationError> {
+        let i = slice::builder_index("pad number", n, PadMap::NUM_PADS)?;
+        self.pads[i] = Optional::Some(pad);
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<M, DeserializationError> {
+        if let Some(model) = self.model {
+            check_model_key(self.cfg.models, PadMapCfg::FIELDS)?;
+            model.check(&self.cfg.model_cfg())?;
+        }
+        let cfg = PadCfg::new(VelocityCfg::Any, ModeCfg::NONE)
+            .keypress(self.cfg.keypress);
+        self.pads
+            .iter()
+            .filter_map(|p| p.as_ref())
+            .try_for_each(|p| p.check(&cfg))?;
+        let missing = de::Error::missing_field;
+        Ok(M::from(PadMap {
+            model: self.model,
+            name: self.name,
+            active_color: self
+                .active_color
+                .ok_or_else(|| missing("active-color"))?,
+            pads: self.pads,
+        }))
+    }
+}
+
+/// Builds a [`PotMap`] in code, running the same checks as deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct PotMapBuilder<M> {
+    cfg: &'static PotMapCfg,
+    model: Option<Model>,
+    name: Name,
+    pots: [Option<Pot>; PotMap::NUM_POTS],
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: From<PotMap>> PotMapBuilder<M> {
+    pub(super) fn new(cfg: &'static PotMapCfg) -> Self {
+        Self {
+            cfg,
+            model: None,
+            name: Name::empty(),
+            pots: [None; PotMap::NUM_POTS],
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the model of the keyboard, so that [`Self::build`] fails if the
+    /// model doesn't have the controls in this map.
+    pub fn model(mut self, model: Model) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn name(mut self, name: Name) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Sets pot `n` (starting at 1), failing if `n` is not between 1 and
+    /// [`PotMap::NUM_POTS`]. Every pot must be set. On devices without a
+    /// global pickup setting, the pot's pickup must be
+    /// [`Pickup::Yes`](crate::Pickup::Yes) or
+    /// [`Pickup::No`](crate::Pickup::No).
+    pub fn pot(
+        mut self,
+        n: usize,
+        pot: Pot,
+    ) -> Result<Self, DeserializationError> {
+        let i = slice::builder_index("pot number", n, PotMap::NUM_POTS)?;
+        self.pots[i] = Some(pot);
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<M, DeserializationError> {
+        if let Some(model) = self.model {
+            check_model_key(self.cfg.models, PotMapCfg::FIELDS)?;
+            model.check(&self.cfg.model_cfg())?;
+        }
+        if self.pots.iter().all(Option::is_none) {
+            return Err(de::Error::missing_field("pots"));
+        }
+        let cfg = PotCfg::new(ModeCfg::NONE).pickup(self.cfg.pickup);
+        let mut pots = [Pot::new(MidiValue::MIN); PotMap::NUM_POTS];
+        for (i, (pot, new)) in pots.iter_mut().zip(self.pots).enumerate() {
+            *pot = new.ok_or_else(|| slice::missing_item(i))?;
+            pot.check(&cfg)?;
+        }
+        Ok(M::from(PotMap {
+            model: self.model,
+            name: self.name,
+            pots,
+        }))
+    }
+}
+
+/// Builds a [`PedalMap`] in code, running the same checks as
+/// deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct PedalMapBuilder<M> {
+    pedal: Option<Pedal>,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: From<PedalMap>> PedalMapBuilder<M> {
+    pub(super) fn new() -> Self {
+        Self {
+            pedal: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn pedal(mut self, pedal: Pedal) -> Self {
+        self.pedal = Some(pedal);
+        self
+    }
+
+    pub fn build(self) -> Result<M, DeserializationError> {
+        let pedal =
+            self.pedal.ok_or_else(|| de::Error::missing_field("cc"))?;
+        Ok(M::from(PedalMap {
+            pedal,
+        }))
+    }
+}
+
+/// Builds a [`FaderMap`] in code, running the same checks as
+/// deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct FaderMapBuilder<M> {
+    cfg: &'static FaderMapCfg,
+    model: Option<Model>,
+    name: Name,
+    active_color: Option<MidiValue>,
+    faders: [Optional<Pot>; FaderMap::NUM_FADERS],
+    buttons: [Optional<Pad>; FaderMap::NUM_BUTTONS],
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: From<FaderMap>> FaderMapBuilder<M> {
+    pub(super) fn new(cfg: &'static FaderMapCfg) -> Self {
+        Self {
+            cfg,
+            model: None,
+            name: Name::empty(),
+            active_color: None,
+            faders: [Optional::None; FaderMap::NUM_FADERS],
+            buttons: [Optional::None; FaderMap::NUM_BUTTONS],
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the model of the keyboard, so that [`Self::build`] fails if the
+    /// model doesn't have the controls in this map.
+    pub fn model(mut self, model: Model) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn name(mut self, name: Name) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn active_color(mut self, color: MidiValue) -> Self {
+        self.active_color = Some(color);
+        self
+    }
+
+    /// Sets fader `n` (starting at 1), failing if `n` is not between 1 and
+    /// [`FaderMap::NUM_FADERS`].
+    pub fn fader(
+        mut self,
+        n: usize,
+        fader: Pot,
+    ) -> Result<Self, DeserializationError> {
+        let i = slice::builder_index("fader number", n, FaderMap::NUM_FADERS)?;
+        self.faders[i] = Optional::Some(fader);
+        Ok(self)
+    }
+
+    /// Sets button `n` (starting at 1), failing if `n` is not between 1 and
+    /// [`FaderMap::NUM_BUTTONS`]. The color of the last button is fixed.
+    pub fn button(
+        mut self,
+        n: usize,
+        button: Pad,
+    ) -> Result<Self, DeserializationError> {
+        let i =
+            slice::builder_index("button number", n, FaderMap::NUM_BUTTONS)?;
+        self.buttons[i] = Optional::Some(button);
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<M, DeserializationError> {
+        if let Some(model) = self.model {
+            check_model_key(self.cfg.models, FaderMapCfg::FIELDS)?;
+            model.check(&self.cfg.model_cfg())?;
+        }
+        let fader_cfg = FaderMap::fader_cfg(ModeCfg::NONE);
+        self.faders
+            .iter()
+            .filter_map(|f| f.as_ref())
+            .try_for_each(|f| f.check(&fader_cfg))?;
+        for (i, button) in self.buttons.iter().enumerate() {
+            if let Optional::Some(button) = button {
+                button.check(&FaderMap::button_cfg(i, ModeCfg::NONE))?;
+            }
+        }
+        let missing = de::Error::missing_field;
+        Ok(M::from(FaderMap {
+            model: self.model,
+            name: self.name,
+            active_color: self
+                .active_color
+                .ok_or_else(|| missing("active-color"))?,
+            faders: self.faders,
+            buttons: self.buttons,
+        }))
     }
 }
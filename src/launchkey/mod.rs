@@ -19,28 +19,87 @@
 
 mod common;
 
+pub use common::Model;
+pub use common::{FaderMapBuilder, PadMapBuilder};
+pub use common::{PedalMapBuilder, PotMapBuilder};
+
 macro_rules! define_device_maps {
     (PadMap) => {
-        #[derive(Clone, Copy, Debug)]
+        #[derive(Clone, Copy, Debug, serde::Serialize)]
+        #[serde(transparent)]
         pub struct PadMap(super::common::PadMap);
 
         impl PadMap {
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> super::PadMapBuilder<Self> {
+                super::PadMapBuilder::new(&PAD_CONFIG)
+            }
+
             pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
             where
                 W: std::io::Write,
             {
                 self.0.compile(DEVICE_ID, writer)
             }
+
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                super::common::PadMap::decompile(DEVICE_ID, reader).map(Self)
+            }
+        }
+
+        impl From<super::common::PadMap> for PadMap {
+            fn from(map: super::common::PadMap) -> Self {
+                Self(map)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            crate::common::ModeCfg<'_>,
+        > for PadMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    &super::common::MapCfg {
+                        map: &PAD_CONFIG,
+                        mode: *config,
+                        section: false,
+                        model: None,
+                    },
+                )
+                .map(Self)
+            }
         }
 
-        impl<'a> serde::Deserialize<'a> for PadMap {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            super::common::SectionCfg<'_>,
+        > for PadMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &super::common::SectionCfg<'_>,
+            ) -> Result<Self, D::Error>
             where
                 D: serde::Deserializer<'a>,
             {
                 crate::parse::config::DeserializeConfig::deserialize(
                     deserializer,
-                    &PAD_CONFIG,
+                    &super::common::MapCfg {
+                        map: &PAD_CONFIG,
+                        mode: config.mode,
+                        section: true,
+                        model: config.model,
+                    },
                 )
                 .map(Self)
             }
@@ -48,26 +107,81 @@ macro_rules! define_device_maps {
     };
 
     (PotMap) => {
-        #[derive(Clone, Copy, Debug)]
+        #[derive(Clone, Copy, Debug, serde::Serialize)]
+        #[serde(transparent)]
         pub struct PotMap(super::common::PotMap);
 
         impl PotMap {
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> super::PotMapBuilder<Self> {
+                super::PotMapBuilder::new(&POT_CONFIG)
+            }
+
             pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
             where
                 W: std::io::Write,
             {
                 self.0.compile(DEVICE_ID, writer)
             }
+
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                super::common::PotMap::decompile(DEVICE_ID, reader).map(Self)
+            }
+        }
+
+        impl From<super::common::PotMap> for PotMap {
+            fn from(map: super::common::PotMap) -> Self {
+                Self(map)
+            }
         }
 
-        impl<'a> serde::Deserialize<'a> for PotMap {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            crate::common::ModeCfg<'_>,
+        > for PotMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
             where
                 D: serde::Deserializer<'a>,
             {
                 crate::parse::config::DeserializeConfig::deserialize(
                     deserializer,
-                    &POT_CONFIG,
+                    &super::common::MapCfg {
+                        map: &POT_CONFIG,
+                        mode: *config,
+                        section: false,
+                        model: None,
+                    },
+                )
+                .map(Self)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            super::common::SectionCfg<'_>,
+        > for PotMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &super::common::SectionCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    &super::common::MapCfg {
+                        map: &POT_CONFIG,
+                        mode: config.mode,
+                        section: true,
+                        model: config.model,
+                    },
                 )
                 .map(Self)
             }
@@ -75,32 +189,156 @@ macro_rules! define_device_maps {
     };
 
     (PedalMap) => {
-        #[derive(Clone, Copy, Debug, serde::Deserialize)]
+        #[derive(Clone, Copy, Debug, serde::Serialize)]
         #[serde(transparent)]
         pub struct PedalMap(super::common::PedalMap);
 
         impl PedalMap {
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> super::PedalMapBuilder<Self> {
+                super::PedalMapBuilder::new()
+            }
+
             pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
             where
                 W: std::io::Write,
             {
                 self.0.compile(DEVICE_ID, writer)
             }
+
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                super::common::PedalMap::decompile(DEVICE_ID, reader).map(Self)
+            }
+        }
+
+        impl From<super::common::PedalMap> for PedalMap {
+            fn from(map: super::common::PedalMap) -> Self {
+                Self(map)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            crate::common::ModeCfg<'_>,
+        > for PedalMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    config,
+                )
+                .map(Self)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            super::common::SectionCfg<'_>,
+        > for PedalMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &super::common::SectionCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    &config.mode,
+                )
+                .map(Self)
+            }
         }
     };
 
     (FaderMap) => {
-        #[derive(Clone, Copy, Debug, serde::Deserialize)]
+        #[derive(Clone, Copy, Debug, serde::Serialize)]
         #[serde(transparent)]
         pub struct FaderMap(super::common::FaderMap);
 
         impl FaderMap {
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> super::FaderMapBuilder<Self> {
+                super::FaderMapBuilder::new(&FADER_CONFIG)
+            }
+
             pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
             where
                 W: std::io::Write,
             {
                 self.0.compile(DEVICE_ID, writer)
             }
+
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                super::common::FaderMap::decompile(DEVICE_ID, reader).map(Self)
+            }
+        }
+
+        impl From<super::common::FaderMap> for FaderMap {
+            fn from(map: super::common::FaderMap) -> Self {
+                Self(map)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            crate::common::ModeCfg<'_>,
+        > for FaderMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    &super::common::MapCfg {
+                        map: &FADER_CONFIG,
+                        mode: *config,
+                        section: false,
+                        model: None,
+                    },
+                )
+                .map(Self)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            super::common::SectionCfg<'_>,
+        > for FaderMap
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &super::common::SectionCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    &super::common::MapCfg {
+                        map: &FADER_CONFIG,
+                        mode: config.mode,
+                        section: true,
+                        model: config.model,
+                    },
+                )
+                .map(Self)
+            }
         }
     };
 
@@ -113,50 +351,303 @@ macro_rules! define_device_maps {
     };
 }
 
+/// Defines `Map`, which combines all of a device's maps into one custom mode,
+/// with a section (named by `$field`) for each map. The controls of each map
+/// are keyed directly in its section, like `[pads.1]`, and the model of the
+/// keyboard is given once, at the top level.
+macro_rules! define_combined_map {
+    ($($field:ident: $map:ident),+ $(,)?) => {
+        /// All of the device's maps in a single custom mode, which compiles
+        /// into a separate SysEx message for each map.
+        #[derive(Clone, Copy, Debug)]
+        pub struct Map {
+            $($field: Option<$map>,)+
+        }
+
+        impl Map {
+            const SECTIONS: &'static [&'static str] =
+                &[$(stringify!($field)),+];
+
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> MapBuilder {
+                MapBuilder(Self {
+                    $($field: None,)+
+                })
+            }
+
+            /// The model of the keyboard, from the first section that has
+            /// one.
+            fn model(&self) -> Option<super::Model> {
+                None$(.or_else(|| self.$field.and_then(|m| m.0.model())))+
+            }
+
+            /// Ensures at least one section is present, and that the sections
+            /// agree on the model.
+            fn check<E: serde::de::Error>(&self) -> Result<(), E> {
+                if $(self.$field.is_none())&&+ {
+                    return Err(super::common::no_sections(Self::SECTIONS));
+                }
+                let model = self.model();
+                $(if let Some(m) = self.$field.and_then(|m| m.0.model()) {
+                    if Some(m) != model {
+                        return Err(E::custom(
+                            "the sections are for different models",
+                        ));
+                    }
+                })+
+                Ok(())
+            }
+
+            pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
+            where
+                W: std::io::Write,
+            {
+                $(if let Some(map) = &self.$field {
+                    map.compile(writer)?;
+                })+
+                Ok(())
+            }
+
+            /// Reads the maps in the order they're compiled. Maps that are
+            /// missing are skipped.
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                use super::common::peek_map_type;
+                $(
+                    let map_type = super::common::$map::MAP_TYPE;
+                    let $field = if peek_map_type(reader, DEVICE_ID)
+                        == Some(map_type)
+                    {
+                        Some($map::decompile(reader)?)
+                    } else {
+                        None
+                    };
+                )+
+                Ok(Self {
+                    $($field,)+
+                })
+            }
+        }
+
+        impl serde::Serialize for Map {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use super::common::Section;
+
+                #[derive(serde::Serialize)]
+                struct Fields<'a> {
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    model: Option<super::Model>,
+                    $(
+                        #[serde(skip_serializing_if = "Option::is_none")]
+                        $field: Option<Section<'a, super::common::$map>>,
+                    )+
+                }
+
+                Fields {
+                    model: self.model(),
+                    $($field: self.$field.as_ref().map(|m| Section(&m.0)),)+
+                }
+                .serialize(serializer)
+            }
+        }
+
+        impl<'a> crate::parse::config::DeserializeConfig<
+            'a,
+            crate::common::ModeCfg<'_>,
+        > for Map
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'a>,
+            {
+                use super::common::{FieldSeed, ModelCfg, SectionCfg};
+                use crate::common::ModeCfg;
+                use crate::parse::config::ConfigSeed;
+                use serde::de;
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                #[serde(field_identifier)]
+                #[serde(expecting = "key")]
+                enum Field {
+                    model,
+                    $($field,)+
+                }
+
+                struct Visitor<'a> {
+                    mode: ModeCfg<'a>,
+                }
+
+                impl<'a> de::Visitor<'a> for Visitor<'_> {
+                    type Value = Map;
+
+                    fn expecting(
+                        &self,
+                        f: &mut std::fmt::Formatter<'_>,
+                    ) -> std::fmt::Result {
+                        write!(f, "custom mode")
+                    }
+
+                    fn visit_map<A>(
+                        self,
+                        mut map: A,
+                    ) -> Result<Self::Value, A::Error>
+                    where
+                        A: de::MapAccess<'a>,
+                    {
+                        let mut model = None;
+                        $(let mut $field = None;)+
+                        while let Some(key) = map.next_key_seed(
+                            FieldSeed::new(MODELS, Map::SECTIONS),
+                        )? {
+                            match key {
+                                Field::model => {
+                                    crate::parse::check_dup(&model, "model")?;
+                                    // The model applies to the sections as
+                                    // they're deserialized.
+                                    $(if $field.is_some() {
+                                        return Err(de::Error::custom(concat!(
+                                            "`model` must come before `",
+                                            stringify!($field),
+                                            "`",
+                                        )));
+                                    })+
+                                    let cfg = ModelCfg::new(MODELS);
+                                    model = Some(map.next_value_seed(
+                                        ConfigSeed::new(&cfg),
+                                    )?);
+                                }
+                                $(Field::$field => {
+                                    crate::parse::check_dup(
+                                        &$field,
+                                        stringify!($field),
+                                    )?;
+                                    let cfg = SectionCfg {
+                                        mode: self.mode,
+                                        model,
+                                    };
+                                    $field = Some(map.next_value_seed(
+                                        ConfigSeed::new(&cfg),
+                                    )?);
+                                })+
+                            }
+                        }
+                        let map = Map {
+                            $($field,)+
+                        };
+                        map.check()?;
+                        Ok(map)
+                    }
+                }
+
+                deserializer.deserialize_map(Visitor {
+                    mode: *config,
+                })
+            }
+        }
+
+        /// Builds a [`Map`] from the device's individual maps.
+        #[derive(Clone, Copy, Debug)]
+        pub struct MapBuilder(Map);
+
+        impl MapBuilder {
+            $(
+                pub fn $field(mut self, map: $map) -> Self {
+                    self.0.$field = Some(map);
+                    self
+                }
+            )+
+
+            /// Builds the map. At least one section must be set.
+            pub fn build(
+                self,
+            ) -> Result<Map, crate::error::DeserializationError> {
+                self.0.check()?;
+                Ok(self.0)
+            }
+        }
+    };
+}
+
 pub mod launchkey_mk3 {
-    use super::common::{PadMapCfg, PotMapCfg};
+    use super::common::{FaderMapCfg, Model, PadMapCfg, PotMapCfg};
     use crate::controls::pot::PickupCfg;
 
-    const DEVICE_ID: u8 = 0x0f;
-    const PAD_CONFIG: PadMapCfg = PadMapCfg::new().keypress(true);
+    pub const DEVICE_ID: u8 = 0x0f;
+    const MODELS: &[Model] = &[
+        Model::Keys25,
+        Model::Keys37,
+        Model::Keys49,
+        Model::Keys61,
+        Model::Keys88,
+    ];
+    const PAD_CONFIG: PadMapCfg =
+        PadMapCfg::new().keypress(true).models(MODELS);
     const POT_CONFIG: PotMapCfg =
-        PotMapCfg::new().pickup(PickupCfg::GlobalAllowed);
+        PotMapCfg::new().pickup(PickupCfg::GlobalAllowed).models(MODELS);
+    const FADER_CONFIG: FaderMapCfg = FaderMapCfg::new().models(MODELS);
 
     define_device_maps!(PadMap, PotMap, PedalMap, FaderMap);
+    define_combined_map! {
+        pads: PadMap,
+        pots: PotMap,
+        faders: FaderMap,
+        pedal: PedalMap,
+    }
 }
 
 pub mod launchkey_mini_mk3 {
-    use super::common::{PadMapCfg, PotMapCfg};
+    use super::common::{Model, PadMapCfg, PotMapCfg};
     use crate::controls::pot::PickupCfg;
 
-    const DEVICE_ID: u8 = 0x0b;
+    pub const DEVICE_ID: u8 = 0x0b;
+    const MODELS: &[Model] = &[];
     const PAD_CONFIG: PadMapCfg = PadMapCfg::new().keypress(false);
     const POT_CONFIG: PotMapCfg =
         PotMapCfg::new().pickup(PickupCfg::BinaryOnly);
 
     define_device_maps!(PadMap, PotMap, PedalMap);
+    define_combined_map!(pads: PadMap, pots: PotMap, pedal: PedalMap);
 }
 
 pub mod flkey {
-    use super::common::{PadMapCfg, PotMapCfg};
+    use super::common::{FaderMapCfg, Model, PadMapCfg, PotMapCfg};
     use crate::controls::pot::PickupCfg;
 
-    const DEVICE_ID: u8 = 0x11;
-    const PAD_CONFIG: PadMapCfg = PadMapCfg::new().keypress(true);
+    pub const DEVICE_ID: u8 = 0x11;
+    const MODELS: &[Model] = &[Model::Keys37, Model::Keys49, Model::Keys61];
+    const PAD_CONFIG: PadMapCfg =
+        PadMapCfg::new().keypress(true).models(MODELS);
     const POT_CONFIG: PotMapCfg =
-        PotMapCfg::new().pickup(PickupCfg::GlobalAllowed);
+        PotMapCfg::new().pickup(PickupCfg::GlobalAllowed).models(MODELS);
+    const FADER_CONFIG: FaderMapCfg = FaderMapCfg::new().models(MODELS);
 
     define_device_maps!(PadMap, PotMap, PedalMap, FaderMap);
+    define_combined_map! {
+        pads: PadMap,
+        pots: PotMap,
+        faders: FaderMap,
+        pedal: PedalMap,
+    }
 }
 
 pub mod flkey_mini {
-    use super::common::{PadMapCfg, PotMapCfg};
+    use super::common::{Model, PadMapCfg, PotMapCfg};
     use crate::controls::pot::PickupCfg;
 
-    const DEVICE_ID: u8 = 0x10;
+    pub const DEVICE_ID: u8 = 0x10;
+    const MODELS: &[Model] = &[];
     const PAD_CONFIG: PadMapCfg = PadMapCfg::new().keypress(false);
     const POT_CONFIG: PotMapCfg =
         PotMapCfg::new().pickup(PickupCfg::GlobalAllowed);
 
     define_device_maps!(PadMap, PotMap, PedalMap);
+    define_combined_map!(pads: PadMap, pots: PotMap, pedal: PedalMap);
 }
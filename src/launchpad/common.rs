@@ -17,16 +17,24 @@
  * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::common::{MidiValue, Name, VelocityCfg};
+use super::generate::Generate;
+use super::layout::{LayoutSeed, Legend};
+use crate::common::{Color, MidiValue, ModeCfg, Name, VelocityCfg};
+use crate::controls::defaults::{self, Defaults, DefaultsCfg};
 use crate::controls::pad::{Pad, PadCfg};
-use crate::controls::pad_fader::{Fader, Orientation};
+use crate::controls::pad_fader::{Fader, FaderCfg, Orientation};
 use crate::controls::{self, Control, Optional};
-use crate::parse::config::ConfigSeed;
+use crate::decompile::{self, Reader};
+use crate::error::DeserializationError;
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
 use crate::parse::{self, primitive, slice};
-use serde::Deserialize;
+use crate::ser::IndexedOptional;
 use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 use std::io::{self, Write};
+use std::marker::PhantomData;
 
 const COMPILE_CONFIG: controls::CompileCfg = controls::CompileCfgRequired {
     null_channel: 0,
@@ -34,21 +42,42 @@ const COMPILE_CONFIG: controls::CompileCfg = controls::CompileCfgRequired {
 .into_cfg()
 .aftertouch(true);
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Transposition {
     Enabled,
     Disabled,
 }
 
+impl Transposition {
+    fn is_enabled(&self) -> bool {
+        *self == Self::Enabled
+    }
+}
+
+impl Serialize for Transposition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bool(self.is_enabled())
+    }
+}
+
 impl<'a> Deserialize<'a> for Transposition {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'a>,
     {
-        primitive::deserialize(deserializer).map(|v| match v {
+        primitive::deserialize(deserializer).map(bool::into)
+    }
+}
+
+impl From<bool> for Transposition {
+    fn from(enabled: bool) -> Self {
+        match enabled {
             true => Self::Enabled,
             false => Self::Disabled,
-        })
+        }
     }
 }
 
@@ -69,6 +98,11 @@ impl Map {
     pub const SIDE_LEN: usize = 8;
     pub const NUM_PADS: usize = Self::SIDE_LEN * Self::SIDE_LEN;
 
+    /// The configuration of every pad, which must have variable velocity.
+    fn pad_cfg(mode: ModeCfg<'_>) -> PadCfg<'_> {
+        PadCfg::new(VelocityCfg::VariableOnly, mode).keypress(true)
+    }
+
     fn fader_orientation(&self) -> Option<Orientation> {
         self.faders
             .iter()
@@ -122,6 +156,166 @@ impl Map {
         ])?;
         writer.write_all(b"\xf7")
     }
+
+    pub fn decompile(
+        device_id: u8,
+        reader: &mut Reader<'_>,
+    ) -> Result<Self, decompile::Error> {
+        reader.expect(b"\xf0\x00\x20\x29\x02")?;
+        reader.expect(&[device_id])?;
+        reader.expect(b"\x20\x00\x45\x40\x7f\x20")?;
+        let name = reader.name()?;
+        reader.expect(&[0x21, 0x01, 0x00])?;
+        let mut pads = [Optional::None; Self::NUM_PADS];
+        for (i, pad) in pads.iter_mut().enumerate() {
+            *pad = Control::decompile(i as u8, reader, &COMPILE_CONFIG)?;
+        }
+        let mut faders = [Optional::<Fader>::None; Self::SIDE_LEN];
+        for (i, fader) in faders.iter_mut().enumerate() {
+            let addr = (Self::NUM_PADS + i) as u8;
+            *fader = Control::decompile(addr, reader, &COMPILE_CONFIG)?;
+        }
+        reader.expect(&[0x00])?;
+        let active_color = reader.value()?;
+        reader.expect(&[0x01, active_color.value()])?;
+        reader.expect(&[0x02, 0x00, 0x06, 0x00, 0x07])?;
+        let trans_oct = match reader.byte()? {
+            0 => Transposition::Disabled,
+            _ => Transposition::Enabled,
+        };
+        reader.expect(&[0x08, 0x00, 0x05])?;
+        let orientation = match reader.byte()? {
+            0x01 => Orientation::Vertical,
+            _ => Orientation::Horizontal,
+        };
+        reader.expect(&[0x04])?;
+        let trans_step = match reader.byte()? {
+            0x40 => Transposition::Disabled,
+            _ => Transposition::Enabled,
+        };
+        reader.expect(b"\xf7")?;
+        for fader in &mut faders {
+            if let Optional::Some(fader) = fader {
+                fader.orientation = orientation;
+            }
+        }
+        Ok(Self {
+            name,
+            active_color,
+            pads,
+            faders,
+            trans_oct,
+            trans_step,
+        })
+    }
+}
+
+impl Serialize for Map {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Fields<'a> {
+            #[serde(skip_serializing_if = "Name::is_empty")]
+            name: Name,
+            active_color: MidiValue,
+            #[serde(rename = "octave-transposition")]
+            #[serde(skip_serializing_if = "Transposition::is_enabled")]
+            trans_oct: Transposition,
+            #[serde(rename = "semitone-transposition")]
+            #[serde(skip_serializing_if = "Transposition::is_enabled")]
+            trans_step: Transposition,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            pads: IndexedOptional<'a, Pad>,
+            #[serde(skip_serializing_if = "IndexedOptional::is_empty")]
+            faders: IndexedOptional<'a, Fader>,
+        }
+
+        Fields {
+            name: self.name,
+            active_color: self.active_color,
+            trans_oct: self.trans_oct,
+            trans_step: self.trans_step,
+            pads: IndexedOptional(&self.pads),
+            faders: IndexedOptional(&self.faders),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Formats the index of a pad as its row and column, like `r3c5`.
+struct PadPosition(usize);
+
+impl Display for PadPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = self.0 / Map::SIDE_LEN + 1;
+        let col = self.0 % Map::SIDE_LEN + 1;
+        write!(f, "r{row}c{col}")
+    }
+}
+
+/// Combines the pads defined by the `pads`, `grid`, `rows`, and `layout`
+/// keys, in that order, which may not define the same pad.
+fn merge_pads<E: de::Error>(
+    sources: [Option<[Optional<Pad>; Map::NUM_PADS]>; 4],
+) -> Result<Option<[Optional<Pad>; Map::NUM_PADS]>, E> {
+    const KEYS: [&str; 4] = ["pads", "grid", "rows", "layout"];
+    let mut merged = None;
+    let mut keys = [None; Map::NUM_PADS];
+    for (key, pads) in KEYS.into_iter().zip(sources) {
+        let Some(pads) = pads else {
+            continue;
+        };
+        let merged = merged.get_or_insert([Optional::None; Map::NUM_PADS]);
+        for (i, pad) in pads.into_iter().enumerate() {
+            let Optional::Some(_) = pad else {
+                continue;
+            };
+            if let Some(old) = keys[i].replace(key) {
+                return Err(E::custom(format_args!(
+                    "pad {} is defined in both `{old}` and `{key}`",
+                    PadPosition(i),
+                )));
+            }
+            merged[i] = pad;
+        }
+    }
+    Ok(merged)
+}
+
+/// Fills the pads that weren't defined explicitly with `generated` pads,
+/// except for those that are part of a fader.
+fn fill_pads(
+    pads: Option<[Optional<Pad>; Map::NUM_PADS]>,
+    generated: Option<[Optional<Pad>; Map::NUM_PADS]>,
+    faders: &Option<[Optional<Fader>; Map::SIDE_LEN]>,
+) -> [Optional<Pad>; Map::NUM_PADS] {
+    let mut pads = pads.unwrap_or([Optional::None; Map::NUM_PADS]);
+    let Some(generated) = generated else {
+        return pads;
+    };
+    let faders = faders.as_ref().map(|a| &a[..]).unwrap_or_default();
+    let in_fader = |i: usize| {
+        faders.iter().enumerate().any(|(n, fader)| match fader {
+            Optional::Some(Fader {
+                orientation: Orientation::Horizontal,
+                ..
+            }) => i / Map::SIDE_LEN == n,
+            Optional::Some(Fader {
+                orientation: Orientation::Vertical,
+                ..
+            }) => i % Map::SIDE_LEN == n,
+            Optional::None => false,
+        })
+    };
+    for (i, (pad, new)) in pads.iter_mut().zip(generated).enumerate() {
+        if let (Optional::None, false) = (&pad, in_fader(i)) {
+            *pad = new;
+        }
+    }
+    pads
 }
 
 fn check_conflict<'a, P, F, E>(pads: P, faders: F) -> Result<(), E>
@@ -144,16 +338,14 @@ where
     impl Display for FmtFader {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self.orientation {
-                Orientation::Horizontal => write!(
-                    f,
-                    "fader {} (horizontal)",
-                    self.index / Map::SIDE_LEN + 1,
-                ),
-                Orientation::Vertical => write!(
-                    f,
-                    "fader {} (vertical)",
-                    self.index % Map::SIDE_LEN + 1,
-                ),
+                Orientation::Horizontal => {
+                    let row = self.index / Map::SIDE_LEN + 1;
+                    write!(f, "fader {row} (horizontal, r{row})")
+                }
+                Orientation::Vertical => {
+                    let col = self.index % Map::SIDE_LEN + 1;
+                    write!(f, "fader {col} (vertical, c{col})")
+                }
             }
         }
     }
@@ -179,7 +371,7 @@ where
                         f,
                         "cannot define both pad {} and {}, \
                         which includes pad {0}",
-                        self.index + 1,
+                        PadPosition(self.index),
                         fader(o),
                     )
                 }
@@ -239,102 +431,295 @@ where
     Ok(())
 }
 
-impl<'a> Deserialize<'a> for Map {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'a>,
-    {
-        #[derive(Deserialize)]
-        #[serde(field_identifier, rename_all = "kebab-case")]
-        #[serde(expecting = "key")]
-        enum Field {
-            Name,
-            ActiveColor,
-            Pads,
-            Faders,
-            #[serde(rename = "octave-transposition")]
-            TransOct,
-            #[serde(rename = "semitone-transposition")]
-            TransStep,
-        }
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "kebab-case")]
+#[serde(expecting = "key")]
+enum Field {
+    Name,
+    ActiveColor,
+    Defaults,
+    Pads,
+    Grid,
+    Rows,
+    Legend,
+    Layout,
+    Generate,
+    Faders,
+    #[serde(rename = "octave-transposition")]
+    TransOct,
+    #[serde(rename = "semitone-transposition")]
+    TransStep,
+}
 
-        struct Visitor;
+struct Visitor<'a> {
+    mode: ModeCfg<'a>,
+}
 
-        impl<'a> de::Visitor<'a> for Visitor {
-            type Value = Map;
+impl<'a> de::Visitor<'a> for Visitor<'_> {
+    type Value = Map;
 
-            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "pad map (table)")
-            }
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pad map (table)")
+    }
 
-            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::MapAccess<'a>,
-            {
-                let mut name = None;
-                let mut active_color = None;
-                let mut pads = None;
-                let mut faders = None;
-                let mut trans_oct = None;
-                let mut trans_step = None;
-                while let Some(field) = map.next_key()? {
-                    match field {
-                        Field::Name => {
-                            parse::check_dup(&name, "name")?;
-                            name = Some(map.next_value()?);
-                        }
-                        Field::ActiveColor => {
-                            parse::check_dup(&active_color, "active-color")?;
-                            active_color = Some(map.next_value()?);
-                        }
-                        Field::Pads => {
-                            parse::check_dup(&pads, "pads")?;
-                            let cfg = PadCfg::new(VelocityCfg::VariableOnly)
-                                .keypress(true);
-                            let b = map.next_value_seed(slice::Seed::new(
-                                || ConfigSeed::new(&cfg),
-                                Map::NUM_PADS,
-                            ))?;
-                            pads = Some(*Box::try_from(b).unwrap());
-                            check_conflict(&pads, &faders)?;
-                        }
-                        Field::Faders => {
-                            parse::check_dup(&faders, "faders")?;
-                            let b = map.next_value_seed(
-                                slice::Seed::basic(Map::SIDE_LEN),
-                            )?;
-                            faders = Some(*Box::try_from(b).unwrap());
-                            check_conflict(&pads, &faders)?;
-                        }
-                        Field::TransOct => {
-                            parse::check_dup(
-                                &trans_oct,
-                                "octave-transposition",
-                            )?;
-                            trans_oct = Some(map.next_value()?);
-                        }
-                        Field::TransStep => {
-                            parse::check_dup(
-                                &trans_step,
-                                "semitone-transposition",
-                            )?;
-                            trans_step = Some(map.next_value()?);
-                        }
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'a>,
+    {
+        let pad_cfg = Map::pad_cfg(self.mode);
+        let fader_cfg = FaderCfg::new(self.mode);
+        let mut name = None;
+        let mut active_color = None;
+        let mut defaults: Option<Defaults> = None;
+        let mut pads = None;
+        let mut grid = None;
+        let mut rows = None;
+        let mut legend: Option<Legend> = None;
+        let mut layout = None;
+        let mut generate: Option<Generate> = None;
+        let mut faders = None;
+        let mut trans_oct = None;
+        let mut trans_step = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Name => {
+                    parse::check_dup(&name, "name")?;
+                    name = Some(map.next_value()?);
+                }
+                Field::ActiveColor => {
+                    parse::check_dup(&active_color, "active-color")?;
+                    active_color = Some(
+                        map.next_value_seed(ConfigSeed::<Color, _>::new(
+                            &self.mode,
+                        ))?
+                        .0,
+                    );
+                }
+                Field::Defaults => {
+                    parse::check_dup(&defaults, "defaults")?;
+                    defaults::check_order(&pads, "pads")?;
+                    defaults::check_order(&grid, "grid")?;
+                    defaults::check_order(&rows, "rows")?;
+                    defaults::check_order(&legend, "legend")?;
+                    defaults::check_order(&layout, "layout")?;
+                    defaults::check_order(&faders, "faders")?;
+                    let cfg = DefaultsCfg::new()
+                        .pad("pad", &pad_cfg)
+                        .fader(&fader_cfg);
+                    defaults =
+                        Some(map.next_value_seed(ConfigSeed::new(&cfg))?);
+                }
+                Field::Pads => {
+                    parse::check_dup(&pads, "pads")?;
+                    let cfg = pad_cfg
+                        .clone()
+                        .defaults(defaults.unwrap_or_default().pad);
+                    let b = map.next_value_seed(
+                        slice::Seed::new(
+                            || ConfigSeed::new(&cfg),
+                            Map::NUM_PADS,
+                        )
+                        .grid(Map::SIDE_LEN),
+                    )?;
+                    pads = Some(*Box::try_from(b).unwrap());
+                    let all = merge_pads([pads, grid, rows, layout])?;
+                    check_conflict(&all, &faders)?;
+                }
+                Field::Grid | Field::Rows => {
+                    let (key, value) = match field {
+                        Field::Grid => ("grid", &mut grid),
+                        _ => ("rows", &mut rows),
+                    };
+                    parse::check_dup(value, key)?;
+                    let cfg = pad_cfg
+                        .clone()
+                        .defaults(defaults.unwrap_or_default().pad);
+                    let row = || {
+                        slice::Seed::new(
+                            || ConfigSeed::new(&cfg),
+                            Map::SIDE_LEN,
+                        )
+                    };
+                    let b = map.next_value_seed(slice::Seed::new(
+                        row,
+                        Map::SIDE_LEN,
+                    ))?;
+                    let mut row_pads = [Optional::None; Map::NUM_PADS];
+                    for (chunk, row) in
+                        row_pads.chunks_mut(Map::SIDE_LEN).zip(b)
+                    {
+                        chunk.copy_from_slice(&row);
                     }
+                    *value = Some(row_pads);
+                    let all = merge_pads([pads, grid, rows, layout])?;
+                    check_conflict(&all, &faders)?;
+                }
+                Field::Legend => {
+                    parse::check_dup(&legend, "legend")?;
+                    if layout.is_some() {
+                        return Err(de::Error::custom(
+                            "`legend` must come before `layout`",
+                        ));
+                    }
+                    let cfg = pad_cfg
+                        .clone()
+                        .defaults(defaults.unwrap_or_default().pad);
+                    legend = Some(map.next_value_seed(ConfigSeed::new(&cfg))?);
+                }
+                Field::Layout => {
+                    parse::check_dup(&layout, "layout")?;
+                    let empty = Legend::default();
+                    layout = Some(map.next_value_seed(LayoutSeed {
+                        legend: legend.as_ref().unwrap_or(&empty),
+                    })?);
+                    let all = merge_pads([pads, grid, rows, layout])?;
+                    check_conflict(&all, &faders)?;
+                }
+                Field::Generate => {
+                    parse::check_dup(&generate, "generate")?;
+                    generate = Some(
+                        map.next_value_seed(ConfigSeed::new(&self.mode))?,
+                    );
+                }
+                Field::Faders => {
+                    parse::check_dup(&faders, "faders")?;
+                    let fader =
+                        fader_cfg.defaults(defaults.unwrap_or_default().fader);
+                    let b = map.next_value_seed(slice::Seed::new(
+                        || ConfigSeed::new(&fader),
+                        Map::SIDE_LEN,
+                    ))?;
+                    faders = Some(*Box::try_from(b).unwrap());
+                    let all = merge_pads([pads, grid, rows, layout])?;
+                    check_conflict(&all, &faders)?;
+                }
+                Field::TransOct => {
+                    parse::check_dup(&trans_oct, "octave-transposition")?;
+                    trans_oct = Some(map.next_value()?);
+                }
+                Field::TransStep => {
+                    parse::check_dup(&trans_step, "semitone-transposition")?;
+                    trans_step = Some(map.next_value()?);
                 }
-                let missing = de::Error::missing_field;
-                Ok(Map {
-                    name: name.unwrap_or_else(Name::empty),
-                    active_color: active_color
-                        .ok_or_else(|| missing("active-color"))?,
-                    pads: pads.unwrap_or([Optional::None; Map::NUM_PADS]),
-                    faders: faders.unwrap_or([Optional::None; Map::SIDE_LEN]),
-                    trans_oct: trans_oct.unwrap_or(Transposition::Enabled),
-                    trans_step: trans_step.unwrap_or(Transposition::Enabled),
-                })
             }
         }
+        let missing = de::Error::missing_field;
+        Ok(Map {
+            name: name.unwrap_or_else(Name::empty),
+            active_color: active_color
+                .ok_or_else(|| missing("active-color"))?,
+            pads: fill_pads(
+                merge_pads([pads, grid, rows, layout])?,
+                generate
+                    .and_then(|g| g.pads(&defaults.unwrap_or_default().pad)),
+                &faders,
+            ),
+            faders: faders.unwrap_or([Optional::None; Map::SIDE_LEN]),
+            trans_oct: trans_oct.unwrap_or(Transposition::Enabled),
+            trans_step: trans_step.unwrap_or(Transposition::Enabled),
+        })
+    }
+}
 
-        deserializer.deserialize_map(Visitor)
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for Map {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_map(Visitor {
+            mode: *config,
+        })
+    }
+}
+
+/// Builds a [`Map`] in code, running the same checks as deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct MapBuilder<M> {
+    map: Map,
+    active_color: Option<MidiValue>,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: From<Map>> MapBuilder<M> {
+    pub(super) fn new() -> Self {
+        Self {
+            map: Map {
+                name: Name::empty(),
+                active_color: MidiValue::MIN,
+                pads: [Optional::None; Map::NUM_PADS],
+                faders: [Optional::None; Map::SIDE_LEN],
+                trans_oct: Transposition::Enabled,
+                trans_step: Transposition::Enabled,
+            },
+            active_color: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn name(mut self, name: Name) -> Self {
+        self.map.name = name;
+        self
+    }
+
+    pub fn active_color(mut self, color: MidiValue) -> Self {
+        self.active_color = Some(color);
+        self
+    }
+
+    /// Sets the pad at `row` and `col` (both starting at 1, from the upper
+    /// left corner), failing if either is not between 1 and
+    /// [`Map::SIDE_LEN`].
+    pub fn pad(
+        mut self,
+        row: usize,
+        col: usize,
+        pad: Pad,
+    ) -> Result<Self, DeserializationError> {
+        let row = slice::builder_index("pad row", row, Map::SIDE_LEN)?;
+        let col = slice::builder_index("pad column", col, Map::SIDE_LEN)?;
+        self.map.pads[row * Map::SIDE_LEN + col] = Optional::Some(pad);
+        Ok(self)
+    }
+
+    /// Sets fader `n` (starting at 1), failing if `n` is not between 1 and
+    /// [`Map::SIDE_LEN`].
+    pub fn fader(
+        mut self,
+        n: usize,
+        fader: Fader,
+    ) -> Result<Self, DeserializationError> {
+        let i = slice::builder_index("fader number", n, Map::SIDE_LEN)?;
+        self.map.faders[i] = Optional::Some(fader);
+        Ok(self)
+    }
+
+    pub fn octave_transposition(mut self, enabled: bool) -> Self {
+        self.map.trans_oct = Transposition::from(enabled);
+        self
+    }
+
+    pub fn semitone_transposition(mut self, enabled: bool) -> Self {
+        self.map.trans_step = Transposition::from(enabled);
+        self
+    }
+
+    pub fn build(self) -> Result<M, DeserializationError> {
+        let cfg = Map::pad_cfg(ModeCfg::NONE);
+        self.map
+            .pads
+            .iter()
+            .filter_map(|p| p.as_ref())
+            .try_for_each(|p| p.check(&cfg))?;
+        check_conflict(&self.map.pads, &self.map.faders)?;
+        let missing = de::Error::missing_field;
+        Ok(M::from(Map {
+            active_color: self
+                .active_color
+                .ok_or_else(|| missing("active-color"))?,
+            ..self.map
+        }))
     }
 }
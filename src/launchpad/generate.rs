@@ -0,0 +1,410 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The `[generate]` table of a Launchpad mode, which fills the grid with
+//! pads generated from a description, like a scale.
+
+use super::common::Map;
+use crate::common::{Channel, Color, MidiNote, MidiValue, ModeCfg, Velocity};
+use crate::controls::Optional;
+use crate::controls::pad::{Behavior, Note, Pad, PadDefaults};
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
+use crate::parse::{self, bounded::BoundedU8};
+use serde::Deserialize;
+use serde::de::{self, Deserializer};
+use std::fmt;
+
+/// Pads generated by the `[generate]` table.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Generate {
+    scale: Option<ScaleLayout>,
+}
+
+impl Generate {
+    /// Generates the pads, which use the channel and behavior in
+    /// `defaults`.
+    pub fn pads(
+        &self,
+        defaults: &PadDefaults,
+    ) -> Option<[Optional<Pad>; Map::NUM_PADS]> {
+        self.scale.map(|s| s.pads(defaults))
+    }
+}
+
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for Generate {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Scale,
+        }
+
+        struct Visitor<'a> {
+            mode: &'a ModeCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = Generate;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "generated pads (table)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut scale = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Scale => {
+                            parse::check_dup(&scale, "scale")?;
+                            scale = Some(map.next_value_seed(
+                                ConfigSeed::new(self.mode),
+                            )?);
+                        }
+                    }
+                }
+                Ok(Generate {
+                    scale,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            mode: config,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scale {
+    Major,
+    Minor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+    Chromatic,
+}
+
+impl Scale {
+    const ALL: [Self; 14] = [
+        Self::Major,
+        Self::Minor,
+        Self::HarmonicMinor,
+        Self::MelodicMinor,
+        Self::Dorian,
+        Self::Phrygian,
+        Self::Lydian,
+        Self::Mixolydian,
+        Self::Locrian,
+        Self::MajorPentatonic,
+        Self::MinorPentatonic,
+        Self::Blues,
+        Self::WholeTone,
+        Self::Chromatic,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::HarmonicMinor => "harmonic-minor",
+            Self::MelodicMinor => "melodic-minor",
+            Self::Dorian => "dorian",
+            Self::Phrygian => "phrygian",
+            Self::Lydian => "lydian",
+            Self::Mixolydian => "mixolydian",
+            Self::Locrian => "locrian",
+            Self::MajorPentatonic => "major-pentatonic",
+            Self::MinorPentatonic => "minor-pentatonic",
+            Self::Blues => "blues",
+            Self::WholeTone => "whole-tone",
+            Self::Chromatic => "chromatic",
+        }
+    }
+
+    /// The semitones above the root of each note in the scale, in
+    /// ascending order.
+    fn intervals(self) -> &'static [u8] {
+        match self {
+            Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Self::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Self::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Self::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Self::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Self::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Self::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Self::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Self::Blues => &[0, 3, 5, 6, 7, 10],
+            Self::WholeTone => &[0, 2, 4, 6, 8, 10],
+            Self::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for Scale {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Scale;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let names = Scale::ALL.map(Scale::name);
+                write!(f, "{}", parse::one_of(names))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Scale::ALL.into_iter().find(|s| s.name() == v).ok_or_else(
+                    || E::invalid_value(de::Unexpected::Str(v), &self),
+                )
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// A grid of notes in a scale, from the `[generate.scale]` table. The root
+/// is in the lower left corner, notes ascend from left to right, and each
+/// row starts `row_offset` above the row below it.
+#[derive(Clone, Copy, Debug)]
+struct ScaleLayout {
+    root: MidiNote,
+    scale: Scale,
+    /// Semitones between rows, or, if `in_key_only` is true, scale degrees.
+    row_offset: u8,
+    /// Whether notes outside the scale are skipped.
+    in_key_only: bool,
+    root_color: MidiValue,
+    scale_color: MidiValue,
+    other_color: MidiValue,
+}
+
+impl ScaleLayout {
+    /// A fourth, in semitones.
+    const CHROMATIC_ROW_OFFSET: u8 = 5;
+    /// A fourth, in degrees of a seven-note scale.
+    const IN_KEY_ROW_OFFSET: u8 = 3;
+    const ROOT_COLOR: MidiValue = match MidiValue::new(45) {
+        Some(v) => v,
+        None => unreachable!(),
+    };
+    const SCALE_COLOR: MidiValue = match MidiValue::new(3) {
+        Some(v) => v,
+        None => unreachable!(),
+    };
+    const OTHER_COLOR: MidiValue = match MidiValue::new(1) {
+        Some(v) => v,
+        None => unreachable!(),
+    };
+
+    /// Gets the note `x` pads to the right of and `y` pads above the root,
+    /// as a number of semitones above the root.
+    fn interval(&self, x: usize, y: usize) -> usize {
+        let offset = usize::from(self.row_offset);
+        if !self.in_key_only {
+            return x + y * offset;
+        }
+        let intervals = self.scale.intervals();
+        let degree = x + y * offset;
+        let octave = degree / intervals.len();
+        octave * 12 + usize::from(intervals[degree % intervals.len()])
+    }
+
+    fn color(&self, interval: usize) -> MidiValue {
+        let interval = (interval % 12) as u8;
+        if interval == 0 {
+            self.root_color
+        } else if self.scale.intervals().contains(&interval) {
+            self.scale_color
+        } else {
+            self.other_color
+        }
+    }
+
+    fn pads(&self, defaults: &PadDefaults) -> [Optional<Pad>; Map::NUM_PADS] {
+        let mut pads = [Optional::None; Map::NUM_PADS];
+        for (i, pad) in pads.iter_mut().enumerate() {
+            let x = i % Map::SIDE_LEN;
+            let y = Map::SIDE_LEN - 1 - i / Map::SIDE_LEN;
+            let interval = self.interval(x, y);
+            let pitch = usize::from(self.root.value()) + interval;
+            let Some(pitch) =
+                u8::try_from(pitch).ok().and_then(MidiValue::new)
+            else {
+                continue;
+            };
+            let note = Note {
+                pitch: MidiNote(pitch),
+                channel: defaults.channel.unwrap_or(Channel::Global),
+                velocity: Velocity::Variable,
+                behavior: defaults.behavior.unwrap_or(Behavior::Momentary),
+            };
+            *pad = Optional::Some(Pad::note(self.color(interval), note));
+        }
+        pads
+    }
+}
+
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for ScaleLayout {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "kebab-case")]
+        #[serde(expecting = "key")]
+        enum Field {
+            Root,
+            Scale,
+            RowOffset,
+            InKeyOnly,
+            RootColor,
+            ScaleColor,
+            OtherColor,
+        }
+
+        struct Visitor<'a> {
+            mode: &'a ModeCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = ScaleLayout;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "scale (table)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                let mut root = None;
+                let mut scale = None;
+                let mut row_offset: Option<BoundedU8<1, 12>> = None;
+                let mut in_key_only = None;
+                let mut root_color = None;
+                let mut scale_color = None;
+                let mut other_color = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Root => {
+                            parse::check_dup(&root, "root")?;
+                            root = Some(map.next_value_seed(
+                                ConfigSeed::new(self.mode.aliases),
+                            )?);
+                        }
+                        Field::Scale => {
+                            parse::check_dup(&scale, "scale")?;
+                            scale = Some(map.next_value()?);
+                        }
+                        Field::RowOffset => {
+                            parse::check_dup(&row_offset, "row-offset")?;
+                            row_offset = Some(map.next_value()?);
+                        }
+                        Field::InKeyOnly => {
+                            parse::check_dup(&in_key_only, "in-key-only")?;
+                            in_key_only = Some(map.next_value()?);
+                        }
+                        Field::RootColor => {
+                            parse::check_dup(&root_color, "root-color")?;
+                            root_color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(self.mode),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::ScaleColor => {
+                            parse::check_dup(&scale_color, "scale-color")?;
+                            scale_color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(self.mode),
+                                )?
+                                .0,
+                            );
+                        }
+                        Field::OtherColor => {
+                            parse::check_dup(&other_color, "other-color")?;
+                            other_color = Some(
+                                map.next_value_seed(
+                                    ConfigSeed::<Color, _>::new(self.mode),
+                                )?
+                                .0,
+                            );
+                        }
+                    }
+                }
+                let in_key_only = in_key_only.unwrap_or(false);
+                Ok(ScaleLayout {
+                    root: root
+                        .ok_or_else(|| de::Error::missing_field("root"))?,
+                    scale: scale.unwrap_or(Scale::Major),
+                    row_offset: row_offset.map_or(
+                        if in_key_only {
+                            ScaleLayout::IN_KEY_ROW_OFFSET
+                        } else {
+                            ScaleLayout::CHROMATIC_ROW_OFFSET
+                        },
+                        BoundedU8::get,
+                    ),
+                    in_key_only,
+                    root_color: root_color.unwrap_or(ScaleLayout::ROOT_COLOR),
+                    scale_color: scale_color
+                        .unwrap_or(ScaleLayout::SCALE_COLOR),
+                    other_color: other_color
+                        .unwrap_or(ScaleLayout::OTHER_COLOR),
+                })
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            mode: config,
+        })
+    }
+}
@@ -0,0 +1,256 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The `layout` and `legend` keys of a Launchpad mode, which draw the grid
+//! of pads as rows of characters.
+//!
+//! When loaded, the `layout` string is split into an array of rows, each of
+//! which is an array of single-character strings, so that errors can point
+//! at the character they occurred in.
+
+use super::common::Map;
+use crate::controls::Optional;
+use crate::controls::pad::{Pad, PadCfg};
+use crate::parse::config::{ConfigSeed, DeserializeConfig};
+use serde::de::{self, DeserializeSeed, Deserializer};
+use std::fmt;
+
+/// The character for an empty pad.
+const EMPTY: char = '.';
+
+/// The pad drawn by each character in a layout.
+#[derive(Clone, Debug, Default)]
+pub struct Legend(Vec<(char, Pad)>);
+
+impl Legend {
+    fn get(&self, c: char) -> Option<Pad> {
+        self.0.iter().find(|(k, _)| *k == c).map(|(_, pad)| *pad)
+    }
+}
+
+/// A key in the legend, which must be a single character.
+struct LegendKey(char);
+
+struct LegendKeyVisitor;
+
+impl de::Visitor<'_> for LegendKeyVisitor {
+    type Value = LegendKey;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a single character other than `.` or whitespace")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let mut chars = v.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c != EMPTY && !c.is_whitespace() => {
+                Ok(LegendKey(c))
+            }
+            _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+impl<'a> de::Deserialize<'a> for LegendKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(LegendKeyVisitor)
+    }
+}
+
+struct LegendVisitor<'a> {
+    cfg: &'a PadCfg<'a>,
+}
+
+impl<'a> de::Visitor<'a> for LegendVisitor<'_> {
+    type Value = Legend;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a table of pads keyed by character")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'a>,
+    {
+        let mut legend = Vec::new();
+        while let Some(LegendKey(c)) = map.next_key()? {
+            legend.push((c, map.next_value_seed(ConfigSeed::new(self.cfg))?));
+        }
+        Ok(Legend(legend))
+    }
+}
+
+impl<'a> DeserializeConfig<'a, PadCfg<'_>> for Legend {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &PadCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_map(LegendVisitor {
+            cfg: config,
+        })
+    }
+}
+
+/// Deserializes a layout into the pads it draws, using the pads in the
+/// legend.
+pub struct LayoutSeed<'a> {
+    pub legend: &'a Legend,
+}
+
+impl<'a> de::Visitor<'a> for LayoutSeed<'_> {
+    type Value = [Optional<Pad>; Map::NUM_PADS];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a layout of {} lines", Map::SIDE_LEN)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'a>,
+    {
+        let mut pads = [Optional::None; Map::NUM_PADS];
+        let mut len = 0;
+        for row in pads.chunks_mut(Map::SIDE_LEN) {
+            let seed = RowSeed {
+                legend: self.legend,
+                row,
+            };
+            if seq.next_element_seed(seed)?.is_none() {
+                return Err(de::Error::invalid_length(len, &self));
+            }
+            len += 1;
+        }
+        while seq.next_element::<de::IgnoredAny>()?.is_some() {
+            len += 1;
+        }
+        if len > Map::SIDE_LEN {
+            return Err(de::Error::invalid_length(len, &self));
+        }
+        Ok(pads)
+    }
+}
+
+impl<'a> DeserializeSeed<'a> for LayoutSeed<'_> {
+    type Value = [Optional<Pad>; Map::NUM_PADS];
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+/// Deserializes one line of a layout into `row`.
+struct RowSeed<'a, 'b> {
+    legend: &'a Legend,
+    row: &'b mut [Optional<Pad>],
+}
+
+impl<'a> de::Visitor<'a> for RowSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a line of {} characters", Map::SIDE_LEN)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'a>,
+    {
+        let legend = self.legend;
+        let mut len = 0;
+        while let Some(pad) = seq.next_element_seed(CellSeed {
+            legend,
+        })? {
+            if let Some(slot) = self.row.get_mut(len) {
+                *slot = pad;
+            }
+            len += 1;
+        }
+        if len != Map::SIDE_LEN {
+            return Err(de::Error::invalid_length(len, &self));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> DeserializeSeed<'a> for RowSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+/// Deserializes a single character of a layout into the pad it draws.
+struct CellSeed<'a> {
+    legend: &'a Legend,
+}
+
+impl<'a> de::Visitor<'a> for CellSeed<'_> {
+    type Value = Optional<Pad>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a single character")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let mut chars = v.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+        };
+        if c == EMPTY {
+            return Ok(Optional::None);
+        }
+        match self.legend.get(c) {
+            Some(pad) => Ok(Optional::Some(pad)),
+            None => Err(E::custom(format_args!(
+                "no legend entry for `{}`",
+                c.escape_default(),
+            ))),
+        }
+    }
+}
+
+impl<'a> DeserializeSeed<'a> for CellSeed<'_> {
+    type Value = Optional<Pad>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
@@ -18,40 +18,74 @@
  */
 
 mod common;
+mod generate;
+mod layout;
+
+pub use common::MapBuilder;
 
 macro_rules! define_device_map {
     () => {
-        #[derive(Clone, Copy, Debug)]
+        #[derive(Clone, Copy, Debug, serde::Serialize)]
+        #[serde(transparent)]
         pub struct Map(super::common::Map);
 
         impl Map {
+            /// Returns a builder for constructing this map in code.
+            pub fn builder() -> super::MapBuilder<Self> {
+                super::MapBuilder::new()
+            }
+
             pub fn compile<W>(&self, writer: &mut W) -> std::io::Result<()>
             where
                 W: std::io::Write,
             {
                 self.0.compile(DEVICE_ID, writer)
             }
+
+            pub fn decompile(
+                reader: &mut crate::decompile::Reader<'_>,
+            ) -> Result<Self, crate::decompile::Error> {
+                super::common::Map::decompile(DEVICE_ID, reader).map(Self)
+            }
+        }
+
+        impl From<super::common::Map> for Map {
+            fn from(map: super::common::Map) -> Self {
+                Self(map)
+            }
         }
 
-        impl<'a> serde::Deserialize<'a> for Map {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        impl<'a>
+            crate::parse::config::DeserializeConfig<
+                'a,
+                crate::common::ModeCfg<'_>,
+            > for Map
+        {
+            fn deserialize<D>(
+                deserializer: D,
+                config: &crate::common::ModeCfg<'_>,
+            ) -> Result<Self, D::Error>
             where
                 D: serde::Deserializer<'a>,
             {
-                serde::Deserialize::deserialize(deserializer).map(Self)
+                crate::parse::config::DeserializeConfig::deserialize(
+                    deserializer,
+                    config,
+                )
+                .map(Self)
             }
         }
     };
 }
 
 pub mod launchpad_mini_mk3 {
-    const DEVICE_ID: u8 = 0x0d;
+    pub const DEVICE_ID: u8 = 0x0d;
 
     define_device_map!();
 }
 
 pub mod launchpad_x {
-    const DEVICE_ID: u8 = 0x0c;
+    pub const DEVICE_ID: u8 = 0x0c;
 
     define_device_map!();
 }
@@ -0,0 +1,562 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Compiler for custom modes on Novation MIDI devices.
+//!
+//! Custom modes are described in TOML; see the [examples] for the format.
+//! [`parse`] turns the text of such a file into a [`CustomMode`], which can
+//! then be [compiled](CustomMode::compile) into a MIDI SysEx message:
+//!
+//! ```
+//! let toml = "version = 2\ndevice = \"launchkey-mk3-pedal\"\ncc = 64\n";
+//! let mode = ncc::parse(toml).unwrap();
+//! let mut syx = Vec::new();
+//! mode.compile(&mut syx).unwrap();
+//! assert_eq!(syx.first(), Some(&0xf0));
+//! ```
+//!
+//! [examples]: https://github.com/taylordotfish/ncc/tree/master/examples
+
+#![deny(unsafe_op_in_unsafe_fn)]
+#![warn(clippy::undocumented_unsafe_blocks)]
+
+use serde::Serialize;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer};
+use serde::ser::Serializer;
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+mod aliases;
+pub mod ansi;
+mod color;
+mod common;
+mod controls;
+pub mod decompile;
+pub mod error;
+mod gm;
+mod keys;
+pub mod launchkey;
+pub mod launchpad;
+mod load;
+mod parse;
+mod ser;
+mod warnings;
+
+use common::ModeCfg;
+pub use common::{Channel, Keypress, MidiChannel, MidiNote, MidiValue};
+pub use common::{Name, NameParseError, Velocity};
+pub use controls::Pedal;
+pub use controls::pad::{Behavior, Cc, Note, Pad, PadAction, Prog};
+pub use controls::pad_fader::{Fader, Mode as FaderMode, Orientation};
+pub use controls::pot::{Pickup, Pot};
+pub use error::{Error, Warning};
+use launchkey::flkey as flk;
+use launchkey::flkey_mini as flkm;
+use launchkey::launchkey_mini_mk3 as lkmm3;
+use launchkey::launchkey_mk3 as lkm3;
+use launchpad::launchpad_mini_mk3 as lpmm3;
+use launchpad::launchpad_x as lpx;
+use parse::config::{ConfigSeed, DeserializeConfig};
+use parse::with_error;
+use warnings::Warnings;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Version {
+    Two,
+}
+
+impl<'a> Deserialize<'a> for Version {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Clone, Copy)]
+        struct Unsupported(u64);
+
+        impl Display for Unsupported {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "unsupported version `{}`: ", self.0)?;
+                write!(f, "version must be 2")
+            }
+        }
+
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Version;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "version number (integer)")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v {
+                    2 => Ok(Version::Two),
+                    _ => Err(E::custom(Unsupported(v))),
+                }
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v.try_into() {
+                    Ok(v) => self.visit_u64(v),
+                    Err(_) => Err(E::invalid_type(
+                        de::Unexpected::Signed(v),
+                        &"positive version number",
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_u32(Visitor)
+    }
+}
+
+macro_rules! define_devices {
+    () => {
+        define_devices! {
+            impl
+            LaunchkeyMiniMk3("launchkey-mini-mk3", lkmm3::Map),
+            LaunchkeyMiniMk3Pads("launchkey-mini-mk3-pads", lkmm3::PadMap),
+            LaunchkeyMiniMk3Pots("launchkey-mini-mk3-pots", lkmm3::PotMap),
+            LaunchkeyMiniMk3Pedal("launchkey-mini-mk3-pedal", lkmm3::PedalMap),
+            LaunchkeyMk3("launchkey-mk3", lkm3::Map),
+            LaunchkeyMk3Pads("launchkey-mk3-pads", lkm3::PadMap),
+            LaunchkeyMk3Pots("launchkey-mk3-pots", lkm3::PotMap),
+            LaunchkeyMk3Pedal("launchkey-mk3-pedal", lkm3::PedalMap),
+            LaunchkeyMk3Faders("launchkey-mk3-faders", lkm3::FaderMap),
+            LaunchpadMiniMk3("launchpad-mini-mk3", lpmm3::Map),
+            LaunchpadX("launchpad-x", lpx::Map),
+            FlkeyMini("flkey-mini", flkm::Map),
+            FlkeyMiniPads("flkey-mini-pads", flkm::PadMap),
+            FlkeyMiniPots("flkey-mini-pots", flkm::PotMap),
+            FlkeyMiniPedal("flkey-mini-pedal", flkm::PedalMap),
+            Flkey("flkey", flk::Map),
+            FlkeyPads("flkey-pads", flk::PadMap),
+            FlkeyPots("flkey-pots", flk::PotMap),
+            FlkeyPedal("flkey-pedal", flk::PedalMap),
+            FlkeyFaders("flkey-faders", flk::FaderMap)
+        }
+    };
+
+    (impl $($variant:ident($str:literal, $map:ty)),*) => {
+        $(
+            #[doc = concat!("The map for `device = \"", $str, "\"`.")]
+            pub type $variant = $map;
+
+            impl From<$map> for CustomMode {
+                fn from(map: $map) -> Self {
+                    Self::$variant(map)
+                }
+            }
+        )*
+
+        /// A supported device (or, for Launchkey and FLkey devices, a
+        /// particular kind of custom mode on that device, or all of them
+        /// combined).
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum Device {
+            $($variant,)*
+        }
+
+        impl Device {
+            /// The name of this device, as used by the `device` key.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $str,)*
+                }
+            }
+        }
+
+        impl FromStr for Device {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($str => Ok(Self::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+
+        impl Device {
+            /// Deserializes the rest of a custom mode for this device.
+            fn deserialize_mode<'a, D>(
+                self,
+                deserializer: D,
+                config: &ModeCfg<'_>,
+            ) -> Result<CustomMode, D::Error>
+            where
+                D: Deserializer<'a>,
+            {
+                match self {
+                    $(Self::$variant => DeserializeConfig::deserialize(
+                        deserializer,
+                        config,
+                    )
+                    .map(CustomMode::$variant),)*
+                }
+            }
+        }
+
+        /// A custom mode for any supported device.
+        #[derive(Clone, Copy, Debug)]
+        pub enum CustomMode {
+            $($variant($map),)*
+        }
+
+        impl CustomMode {
+            /// Compiles this custom mode into a MIDI SysEx message.
+            pub fn compile<W>(&self, writer: &mut W) -> io::Result<()>
+            where
+                W: Write,
+            {
+                match self {
+                    $(Self::$variant(m) => m.compile(writer),)*
+                }
+            }
+
+            /// Reads a custom mode for `device` previously written by
+            /// [`Self::compile`].
+            pub fn decompile(
+                device: Device,
+                reader: &mut decompile::Reader<'_>,
+            ) -> Result<Self, decompile::Error> {
+                match device {
+                    $(Device::$variant => {
+                        <$map>::decompile(reader).map(Self::$variant)
+                    })*
+                }
+            }
+
+            /// The device this custom mode is for.
+            pub fn device(&self) -> Device {
+                match self {
+                    $(Self::$variant(_) => Device::$variant,)*
+                }
+            }
+
+        }
+
+        impl Serialize for CustomMode {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                #[derive(Serialize)]
+                struct Fields<'a, T> {
+                    version: Version,
+                    device: Device,
+                    #[serde(flatten)]
+                    map: &'a T,
+                }
+
+                match self {
+                    $(Self::$variant(m) => Fields {
+                        version: Version::Two,
+                        device: self.device(),
+                        map: m,
+                    }
+                    .serialize(serializer),)*
+                }
+            }
+        }
+    };
+}
+
+define_devices!();
+
+impl Serialize for Version {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Two => serializer.serialize_u8(2),
+        }
+    }
+}
+
+impl Serialize for Device {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'a> Deserialize<'a> for Device {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Clone, Copy)]
+        struct Unknown<'a>(&'a str);
+
+        impl Display for Unknown<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "unknown device \"{}\"", self.0)
+            }
+        }
+
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Device;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "device name (string)")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                v.parse().map_err(|_| E::custom(Unknown(v)))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// Deserializes a custom mode without aliases, as when decompiling. Use
+/// [`parse_input`] to load a file that defines them.
+impl<'a> Deserialize<'a> for CustomMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        DeserializeConfig::deserialize(deserializer, &ModeCfg::NONE)
+    }
+}
+
+impl<'a> DeserializeConfig<'a, ModeCfg<'_>> for CustomMode {
+    fn deserialize<D>(
+        deserializer: D,
+        config: &ModeCfg<'_>,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        #[derive(Clone, Copy)]
+        struct UnexpectedField<'a> {
+            unexp: &'a str,
+            exp: &'static str,
+        }
+
+        impl Display for UnexpectedField<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "unexpected key `{}`; expected `{}`",
+                    self.unexp, self.exp,
+                )
+            }
+        }
+
+        struct FieldSeed(&'static str);
+
+        impl de::Visitor<'_> for FieldSeed {
+            type Value = ();
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "key")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<(), E> {
+                if v == self.0 {
+                    Ok(())
+                } else {
+                    Err(E::custom(UnexpectedField {
+                        unexp: v,
+                        exp: self.0,
+                    }))
+                }
+            }
+        }
+
+        impl<'a> DeserializeSeed<'a> for FieldSeed {
+            type Value = ();
+
+            fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+            where
+                D: Deserializer<'a>,
+            {
+                deserializer.deserialize_str(self)
+            }
+        }
+
+        struct Visitor<'a> {
+            mode: &'a ModeCfg<'a>,
+        }
+
+        impl<'a> de::Visitor<'a> for Visitor<'_> {
+            type Value = CustomMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "version and device")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'a>,
+            {
+                map.next_key_seed(FieldSeed("version"))?
+                    .ok_or_else(|| de::Error::missing_field("version"))?;
+                let version: Version = map.next_value()?;
+                assert_eq!(version, Version::Two);
+                map.next_key_seed(FieldSeed("device"))?
+                    .ok_or_else(|| de::Error::missing_field("device"))?;
+                let device: Device = map.next_value()?;
+                device.deserialize_mode(
+                    MapAccessDeserializer::new(map),
+                    self.mode,
+                )
+            }
+        }
+
+        deserializer.deserialize_map(Visitor {
+            mode: config,
+        })
+    }
+}
+
+/// The input text of a custom mode, used when showing errors.
+#[derive(Clone, Copy)]
+pub struct Input<'a> {
+    pub path: &'a Path,
+    pub text: &'a str,
+}
+
+impl CustomMode {
+    /// Returns the text of a TOML file describing this custom mode.
+    ///
+    /// This fails only if the custom mode can't be represented as TOML.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+}
+
+/// Parses the text of a TOML file into a custom mode.
+///
+/// Files named by a top-level `extends` key are resolved relative to the
+/// current directory; use [`parse_input`] to resolve them relative to the
+/// file being parsed instead.
+///
+/// The returned error can be displayed with [`Error::show`], which points
+/// at the relevant part of `text`.
+pub fn parse(text: &str) -> Result<CustomMode, Error> {
+    parse_input(
+        Input {
+            path: "".as_ref(),
+            text,
+        },
+        &[],
+    )
+}
+
+/// Parses a TOML file into a custom mode.
+///
+/// Files named by a top-level `extends` key are resolved relative to the
+/// directory containing `input.path`. Their keys are merged into the custom
+/// mode, with keys in the file that names them taking precedence; tables are
+/// merged key by key.
+///
+/// `vars` defines variables in addition to those in the `[vars]` table,
+/// overriding any with the same name. Each value is parsed as a TOML value
+/// (e.g., `16` is an integer) or, if that fails, used as a string.
+///
+/// The returned error can be displayed with [`Error::show`], which points at
+/// the relevant part of `input` or of the extended file the error occurred in.
+pub fn parse_input(
+    input: Input<'_>,
+    vars: &[(&str, &str)],
+) -> Result<CustomMode, Error> {
+    parse_input_with_warnings(input, vars).map(|(mode, _)| mode)
+}
+
+/// Parses a TOML file into a custom mode, like [`parse_input`], and also
+/// returns warnings about parts of the file that were accepted but may not
+/// do what was intended (e.g., hex colors far from any color in the
+/// palette).
+///
+/// Warnings can be displayed with [`Warning::show`], like errors.
+pub fn parse_input_with_warnings(
+    input: Input<'_>,
+    vars: &[(&str, &str)],
+) -> Result<(CustomMode, Vec<Warning>), Error> {
+    let mut loader = load::Loader::new(input, vars);
+    let doc = loader.load()?;
+    let warnings = Warnings::new();
+    let cfg = ModeCfg {
+        aliases: loader.aliases(),
+        warnings: Some(&warnings),
+    };
+    let mode = with_error::deserialize_seed(
+        ConfigSeed::<CustomMode, _>::new(&cfg),
+        toml_edit::de::Deserializer::from(doc),
+    )
+    .map_err(|(err, toml_err)| loader.locate(Error::new(err, toml_err)))?;
+    let warnings = warnings
+        .into_vec()
+        .into_iter()
+        .map(|w| w.map(|e| loader.locate(e)))
+        .collect();
+    Ok((mode, warnings))
+}
+
+/// This function silences dead code warnings for items that are an intentional
+/// part of the public interface of a module or type, but are currently unused
+/// in this crate.
+///
+/// The advantage of silencing the warnings this way instead of adding
+/// `#[allow(dead_code)]` to each of the items is that unused code in the
+/// private implementation of each item will still be detected.
+#[allow(dead_code, unused_imports)]
+fn allow_unused<T>()
+where
+    for<'a> T: Deserialize<'a> + Deserializer<'a> + Write,
+    for<'a> T: parse::wrap::ValueWrapper<'a, T>,
+{
+    let _ = ansi::AnsiWriter::<T>::fancy;
+    let _ = ansi::AnsiWriter::<T>::into_inner;
+    let _ = ansi::FmtWriter::<T>::borrow;
+    let _ = ansi::FmtWriter::<T>::with_fmt;
+    let _ = common::MidiChannel::number;
+    let _ = parse::bounded::BoundedU16::<0, 0>::get;
+    let _ = parse::bounded::BoundedU32::<0, 0>::get;
+    let _ = parse::bounded::BoundedU64::<0, 0>::get;
+    let _ = parse::error::WrappedError::<T, T>::transpose::<T>;
+    let _ = parse::slice::deserialize::<T, T>;
+    let _ = parse::try_none::<T>;
+    let _ =
+        parse::with_error::deserialize::<error::DeserializationError, T, T>;
+    let _ = parse::wrap::deserialize::<T, T, T>;
+    use crate::controls::{Pad as _, Pot as _};
+}
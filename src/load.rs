@@ -0,0 +1,1075 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Loading of custom modes before they are deserialized: files named by
+//! `extends` are merged in, references to variables are substituted, aliases
+//! are read, range keys like `"1-16"` in pad tables are expanded, and the
+//! `layout` string of a Launchpad mode is split into characters.
+
+use super::aliases::Aliases;
+use super::common::{CcNumber, MidiNote, NoteName, ProgramNumber};
+use super::error::{DeserializationError, Error};
+use super::gm;
+use super::parse::config::DeserializeConfig;
+use super::{Device, Input};
+use serde::de::{self, IntoDeserializer, Unexpected};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{
+    Array, DocumentMut, Formatted, ImDocument, Item, Key, Table, Value,
+};
+
+/// The top-level key that names the files a custom mode inherits from.
+const EXTENDS_KEY: &str = "extends";
+/// The top-level table that defines variables.
+const VARS_KEY: &str = "vars";
+/// The top-level table that defines aliases.
+const ALIASES_KEY: &str = "aliases";
+/// Starts the text of a key or value that stands in for a generated one
+/// until it's parsed. A null character can't appear in a bare key, and no
+/// string written in a file lacks a span, so placeholders can't be confused
+/// with anything else.
+const PLACEHOLDER: &str = "\0generated:";
+/// The key of the tables whose range keys are expanded.
+const PADS_KEY: &str = "pads";
+/// The key of a Launchpad mode whose string value draws the grid of pads,
+/// one character per pad.
+const LAYOUT_KEY: &str = "layout";
+/// The largest index a range key may end at, which keeps huge ranges from
+/// taking too long to expand. No device has more controls of one kind.
+const MAX_RANGE_END: usize = 128;
+
+/// A file loaded because it was named by `extends`.
+struct File {
+    path: PathBuf,
+    text: String,
+    /// Where `text` starts in the spans of the combined document.
+    offset: usize,
+}
+
+/// A key or value that was generated from another part of the document:
+/// the value of a referenced variable, an item expanded from a range, or a
+/// character of a layout.
+struct Substitution {
+    /// The span of the new key or value.
+    value: Range<usize>,
+    /// The span of the text it was generated from, where errors in it are
+    /// reported.
+    reference: Range<usize>,
+}
+
+/// A key or value generated from another part of the document, before it's
+/// parsed by [`Loader::parse_generated`].
+enum Generated {
+    Value(Value),
+    /// The key for an index in a range.
+    Key(usize),
+    /// A layout split into lines of characters.
+    Layout(Vec<Line>),
+}
+
+/// Generated keys and values, once parsed, by index.
+#[derive(Default)]
+struct Parsed {
+    keys: HashMap<usize, Key>,
+    values: HashMap<usize, Value>,
+}
+
+/// A line of a layout: each (non-whitespace) character, and where it was
+/// written, if known.
+type Line = Vec<(char, Option<Range<usize>>)>;
+
+type Vars = HashMap<String, Value>;
+/// Paths named by `extends`, along with their spans.
+type Paths = Vec<(String, Option<Range<usize>>)>;
+
+/// Loads a custom mode along with the files it extends.
+///
+/// Each extended file is parsed as if it were preceded by all of the text
+/// loaded before it (replaced with spaces), so spans from different files
+/// never overlap and each can be traced back to the file it came from.
+/// Values substituted for variables and keys and values expanded from ranges
+/// are given spans the same way, after all of the files: each is inserted as
+/// a placeholder, and once a pass over the document is done, all of them are
+/// parsed together and put in place.
+pub struct Loader<'a> {
+    input: Input<'a>,
+    /// Variables defined outside of the custom mode.
+    defines: &'a [(&'a str, &'a str)],
+    files: Vec<File>,
+    substitutions: Vec<Substitution>,
+    /// Keys and values generated since they were last parsed, along with
+    /// the text they were generated from.
+    generated: Vec<(Generated, Option<Range<usize>>)>,
+    aliases: Aliases,
+    /// Canonical paths of the files currently being loaded, used to detect
+    /// cycles.
+    stack: Vec<PathBuf>,
+    /// The end of the text loaded so far, plus one.
+    end: usize,
+}
+
+impl<'a> Loader<'a> {
+    pub fn new(input: Input<'a>, defines: &'a [(&'a str, &'a str)]) -> Self {
+        Self {
+            input,
+            defines,
+            files: Vec::new(),
+            substitutions: Vec::new(),
+            generated: Vec::new(),
+            aliases: Aliases::default(),
+            stack: Vec::new(),
+            end: input.text.len() + 1,
+        }
+    }
+
+    /// Loads the custom mode, merging in the files it extends and
+    /// substituting variables. The aliases it defines are then available
+    /// from [`Self::aliases`].
+    pub fn load(&mut self) -> Result<DocumentMut, Error> {
+        self.load_root().map_err(|e| self.locate(e))
+    }
+
+    fn load_root(&mut self) -> Result<DocumentMut, Error> {
+        let Input {
+            path,
+            text,
+        } = self.input;
+        self.stack.extend(path.canonicalize().ok());
+        let mut table = self.load_text(path, text)?;
+        let vars = self.vars(table.remove(VARS_KEY))?;
+        self.substitute_table(&mut table, &vars)?;
+        self.parse_generated(&mut table);
+        self.aliases = load_aliases(table.remove(ALIASES_KEY))?;
+        self.expand_pads(&mut table)?;
+        self.split_layouts(&mut table);
+        self.parse_generated(&mut table);
+        order(&mut table);
+        Ok(table.into())
+    }
+
+    /// The aliases defined by the custom mode, once loaded.
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// Makes the span of `error` relative to the file it occurred in.
+    pub fn locate(&self, mut error: Error) -> Error {
+        let Some(span) = error.span() else {
+            return error;
+        };
+        let span = self.resolve(span);
+        let start = span.start;
+        error = error.with_span(span);
+        match self.files.iter().rfind(|f| f.offset <= start) {
+            Some(f) => error.in_file(f.path.clone(), f.text.clone(), f.offset),
+            None => error,
+        }
+    }
+
+    /// Follows the substitutions that generated `span`, if any, to the span
+    /// of the text they were generated from.
+    ///
+    /// Generated text may itself have been generated from other generated
+    /// text (e.g., a range expanded from a variable's value), so
+    /// substitutions are followed until the span refers to text that was
+    /// actually written. A substitution's reference always comes before its
+    /// value, so this ends.
+    fn resolve(&self, mut span: Range<usize>) -> Range<usize> {
+        while let Some(s) =
+            self.substitutions.iter().find(|s| s.value.contains(&span.start))
+        {
+            span = s.reference.clone();
+        }
+        span
+    }
+
+    fn load_text(&mut self, path: &Path, text: &str) -> Result<Table, Error> {
+        let doc =
+            ImDocument::parse(text).map_err(|e| Error::new(None, e.into()))?;
+        let mut table = doc.as_table().clone();
+        let Some(item) = table.remove(EXTENDS_KEY) else {
+            return Ok(table);
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut base = Table::new();
+        for (name, span) in paths(&item)? {
+            merge(&mut base, &self.load_path(dir.join(name), span)?);
+        }
+        merge(&mut base, &table);
+        Ok(base)
+    }
+
+    fn load_path(
+        &mut self,
+        path: PathBuf,
+        span: Option<Range<usize>>,
+    ) -> Result<Table, Error> {
+        let read_error = |e| {
+            let msg = format!("could not read `{}`: {e}", path.display());
+            Error::custom(msg, span.clone())
+        };
+        let canonical = path.canonicalize().map_err(read_error)?;
+        if self.stack.contains(&canonical) {
+            return Err(Error::custom(
+                format!(
+                    "circular `{EXTENDS_KEY}`: `{}` extends itself",
+                    path.display()
+                ),
+                span,
+            ));
+        }
+        let text = std::fs::read_to_string(&path).map_err(read_error)?;
+
+        let offset = self.end;
+        self.end += text.len() + 1;
+        // A byte order mark is allowed only at the very start of a document.
+        let body = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let padded = " ".repeat(offset + text.len() - body.len()) + body;
+        self.files.push(File {
+            path: path.clone(),
+            text,
+            offset,
+        });
+
+        self.stack.push(canonical);
+        let table = self.load_text(&path, &padded);
+        self.stack.pop();
+        table
+    }
+
+    /// Gets the variables defined by the `[vars]` table and by `defines`.
+    fn vars(&self, item: Option<Item>) -> Result<Vars, Error> {
+        let mut vars = Vars::new();
+        if let Some(item) = item {
+            let table = table(&item)
+                .ok_or_else(|| invalid_type(&item, "a table of variables"))?;
+            for (name, item) in table.iter() {
+                let value = item
+                    .as_value()
+                    .ok_or_else(|| invalid_type(item, "a value"))?;
+                vars.insert(name.to_owned(), value.clone());
+            }
+        }
+        for &(name, value) in self.defines {
+            let value = value.parse().unwrap_or_else(|_| Value::from(value));
+            vars.insert(name.to_owned(), value);
+        }
+        Ok(vars)
+    }
+
+    fn substitute_table(
+        &mut self,
+        table: &mut Table,
+        vars: &Vars,
+    ) -> Result<(), Error> {
+        table.iter_mut().try_for_each(|(_, item)| match item {
+            Item::Value(v) => self.substitute(v, vars),
+            Item::Table(t) => self.substitute_table(t, vars),
+            Item::ArrayOfTables(a) => {
+                a.iter_mut().try_for_each(|t| self.substitute_table(t, vars))
+            }
+            Item::None => Ok(()),
+        })
+    }
+
+    fn substitute(
+        &mut self,
+        value: &mut Value,
+        vars: &Vars,
+    ) -> Result<(), Error> {
+        match value {
+            Value::String(s) => {
+                if let Some(v) = self.expand(s, vars)? {
+                    *value = v;
+                }
+                Ok(())
+            }
+            Value::Array(a) => {
+                a.iter_mut().try_for_each(|v| self.substitute(v, vars))
+            }
+            Value::InlineTable(t) => {
+                t.iter_mut().try_for_each(|(_, v)| self.substitute(v, vars))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Substitutes the variables referenced by `string`.
+    ///
+    /// If `string` consists of a single reference (e.g., `"${channel}"`),
+    /// it's replaced by the variable's value, whatever its type. Otherwise,
+    /// each reference is replaced by the variable's value as a string, and
+    /// each `$${` is replaced by a literal `${`.
+    fn expand(
+        &mut self,
+        string: &Formatted<String>,
+        vars: &Vars,
+    ) -> Result<Option<Value>, Error> {
+        let text = string.value();
+        if !text.contains("${") {
+            return Ok(None);
+        }
+        let span = string.span();
+        let lookup = |name: &str| {
+            vars.get(name).ok_or_else(|| {
+                let msg = format!("undefined variable `{name}`");
+                Error::custom(msg, span.clone())
+            })
+        };
+
+        if let Some(name) = text
+            .strip_prefix("${")
+            .and_then(|s| s.strip_suffix('}'))
+            .filter(|s| !s.contains('}'))
+        {
+            let value = lookup(name)?.clone();
+            return Ok(Some(self.respan(value, span)));
+        }
+
+        let mut expanded = String::new();
+        let mut rest = text.as_str();
+        while let Some(i) = rest.find("${") {
+            if let Some(before) = rest[..i].strip_suffix('$') {
+                expanded += before;
+                expanded += "${";
+                rest = &rest[i + 2..];
+                continue;
+            }
+            expanded += &rest[..i];
+            let Some((name, after)) = rest[i + 2..].split_once('}') else {
+                return Err(Error::custom("unterminated `${`", span));
+            };
+            match lookup(name)? {
+                Value::String(v) => expanded += v.value(),
+                Value::Integer(v) => expanded += &v.value().to_string(),
+                Value::Float(v) => expanded += &v.value().to_string(),
+                Value::Boolean(v) => expanded += &v.value().to_string(),
+                Value::Datetime(v) => expanded += &v.value().to_string(),
+                v => {
+                    let msg = format!(
+                        "variable `{name}` is {}, which can't be part of a \
+                        string",
+                        unexpected(&Item::Value(v.clone())),
+                    );
+                    return Err(Error::custom(msg, span));
+                }
+            }
+            rest = after;
+        }
+        expanded += rest;
+        Ok(Some(self.respan(Value::from(expanded), span)))
+    }
+
+    /// Gives `value` a span of its own, so that errors in it can be traced
+    /// back to `reference`, the text it was generated from. Returns a
+    /// placeholder until [`Self::parse_generated`] is called.
+    fn respan(
+        &mut self,
+        mut value: Value,
+        reference: Option<Range<usize>>,
+    ) -> Value {
+        value.decor_mut().clear();
+        let i = self.generate(Generated::Value(value), reference);
+        Value::from(placeholder(i))
+    }
+
+    /// Creates the key for `index`, with a span that can be traced back to
+    /// `reference`, the range key it was expanded from. Returns a
+    /// placeholder until [`Self::parse_generated`] is called.
+    fn index_key(
+        &mut self,
+        index: usize,
+        reference: Option<Range<usize>>,
+    ) -> Key {
+        let i = self.generate(Generated::Key(index), reference);
+        Key::new(placeholder(i))
+    }
+
+    /// Adds `generated` to the keys and values waiting to be parsed, and
+    /// returns its index.
+    fn generate(
+        &mut self,
+        generated: Generated,
+        reference: Option<Range<usize>>,
+    ) -> usize {
+        self.generated.push((generated, reference));
+        self.generated.len() - 1
+    }
+
+    /// Parses the keys and values generated since the last call, all at
+    /// once, as if their text came after all of the text loaded so far, and
+    /// puts them in place of their placeholders in `table`.
+    fn parse_generated(&mut self, table: &mut Table) {
+        if self.generated.is_empty() {
+            return;
+        }
+        let mut text = format!("{:1$}", "", self.end);
+        for (i, (generated, _)) in self.generated.iter().enumerate() {
+            let line = match generated {
+                Generated::Value(value) => format!("{i}={value}"),
+                Generated::Key(index) => format!("{i}={{{index}=0}}"),
+                Generated::Layout(lines) => {
+                    let array: Array = lines
+                        .iter()
+                        .map(|line| {
+                            line.iter()
+                                .map(|(c, _)| c.to_string())
+                                .collect::<Array>()
+                        })
+                        .collect();
+                    format!("{i}={array}")
+                }
+            };
+            text += &line;
+            text += "\n";
+        }
+        self.end = text.len() + 1;
+        let doc = ImDocument::parse(text)
+            .expect("generated text should be valid TOML");
+
+        let generated = std::mem::take(&mut self.generated);
+        let mut parsed = Parsed::default();
+        for (i, (generated, reference)) in generated.into_iter().enumerate() {
+            let item = &doc[i.to_string().as_str()];
+            let span = match generated {
+                Generated::Key(index) => {
+                    let table = item.as_inline_table();
+                    let key = table.and_then(|t| t.key(&index.to_string()));
+                    let key = key.expect("key should be present");
+                    parsed.keys.insert(i, key.clone());
+                    key.span()
+                }
+                Generated::Layout(lines) => {
+                    let array = item.as_array();
+                    let array = array.expect("item should be an array");
+                    self.respan_layout(array, &lines);
+                    parsed.values.insert(i, array.clone().into());
+                    array.span()
+                }
+                Generated::Value(_) => {
+                    let value = item.as_value();
+                    let value = value.expect("item should be a value");
+                    parsed.values.insert(i, value.clone());
+                    value.span()
+                }
+            };
+            // Pushed after the substitutions for each line and character of
+            // a layout, so that they're found first.
+            if let (Some(value), Some(reference)) = (span, reference) {
+                self.substitutions.push(Substitution {
+                    value,
+                    reference,
+                });
+            }
+        }
+        fill_table(table, &mut parsed);
+    }
+
+    /// Gets the text that `span` refers to, unless it was generated.
+    fn source(&self, span: Range<usize>) -> Option<&str> {
+        if span.end <= self.input.text.len() {
+            return self.input.text.get(span);
+        }
+        let file = self.files.iter().rfind(|f| f.offset <= span.start)?;
+        file.text.get(span.start - file.offset..span.end - file.offset)
+    }
+
+    /// Splits the `layout` string of a Launchpad mode into an array of
+    /// lines, each of which is an array of the (non-whitespace) characters
+    /// in the line. Other custom modes are left alone.
+    fn split_layouts(&mut self, table: &mut Table) {
+        let device = table.get("device").and_then(Item::as_str);
+        if !matches!(
+            device.and_then(|d| d.parse().ok()),
+            Some(Device::LaunchpadX | Device::LaunchpadMiniMk3),
+        ) {
+            return;
+        }
+        if let Some(item) = table.get_mut(LAYOUT_KEY) {
+            if let Item::Value(Value::String(s)) = item {
+                *item = Item::Value(self.split_layout(s));
+            }
+        }
+    }
+
+    /// Splits a `layout` string into lines of characters. Each character,
+    /// and each line, is given a span that refers to where it was written in
+    /// the string, if possible.
+    fn split_layout(&mut self, string: &Formatted<String>) -> Value {
+        // The string may have been substituted for a variable, in which case
+        // its characters can't be traced any further than the reference.
+        let span = string.span().map(|s| self.resolve(s));
+        let text = string.value();
+        let count = text.chars().count();
+        let raw = span.clone().and_then(|s| {
+            let start = s.start;
+            Some((start, char_spans(self.source(s)?)?))
+        });
+        let spans: Vec<_> = match raw {
+            // If the raw text doesn't match up with the characters, errors
+            // are reported at the whole string instead.
+            Some((start, spans)) if spans.len() == count => spans
+                .into_iter()
+                .map(|s| Some(s.start + start..s.end + start))
+                .collect(),
+            _ => vec![None; count],
+        };
+
+        let mut lines = vec![Vec::new()];
+        for (c, span) in text.chars().zip(spans) {
+            if c == '\n' {
+                lines.push(Vec::new());
+            } else if !c.is_whitespace() {
+                lines.last_mut().unwrap().push((c, span));
+            }
+        }
+        lines.retain(|line| !line.is_empty());
+
+        let i = self.generate(Generated::Layout(lines), span);
+        Value::from(placeholder(i))
+    }
+
+    /// Traces each character of a generated layout, and each line, back to
+    /// where it was written in the string, using the spans in `lines`.
+    fn respan_layout(&mut self, array: &Array, lines: &[Line]) {
+        for (value, line) in array.iter().zip(lines) {
+            let values = value.as_array().expect("item should be an array");
+            for (value, (_, span)) in values.iter().zip(line) {
+                if let (Some(value), Some(reference)) = (value.span(), span) {
+                    self.substitutions.push(Substitution {
+                        value,
+                        reference: reference.clone(),
+                    });
+                }
+            }
+            let first = line.first().and_then(|(_, s)| s.as_ref());
+            let last = line.last().and_then(|(_, s)| s.as_ref());
+            if let (Some(value), Some(first), Some(last)) =
+                (value.span(), first, last)
+            {
+                self.substitutions.push(Substitution {
+                    value,
+                    reference: first.start..last.end,
+                });
+            }
+        }
+    }
+
+    /// Expands the range keys in the `pads` table of `table`, which is also
+    /// the pads section of a combined custom mode. Range keys elsewhere are
+    /// left alone.
+    fn expand_pads(&mut self, table: &mut Table) -> Result<(), Error> {
+        if let Some(pads) = table.get_mut(PADS_KEY).and_then(pads_table) {
+            self.expand_ranges(pads)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces each range key in `table`, like `"1-16"`, with a key for
+    /// each index in the range. The value for each index is a copy of the
+    /// range's value, except that arrays are replaced by one of their
+    /// elements, cycling through them, and tables like `{ start = 20, step =
+    /// 2 }` are replaced by successive values in the progression.
+    fn expand_ranges(&mut self, table: &mut Table) -> Result<(), Error> {
+        let entries: Vec<_> = table
+            .iter()
+            .map(|(name, item)| {
+                let key = table.key(name).expect("key should be present");
+                (key.clone(), item.clone())
+            })
+            .collect();
+        table.clear();
+        // The key that defined each index.
+        let mut defined: HashMap<usize, Key> = HashMap::new();
+        for (key, item) in entries {
+            let Some(range) = range_key(key.get()) else {
+                if let Ok(index) = key.get().parse() {
+                    check_overlap(&mut defined, index, &key)?;
+                }
+                table.insert_formatted(&key, item);
+                continue;
+            };
+            let (start, end) = range;
+            let invalid = |reason| {
+                let msg = format!("invalid range `{}`: {reason}", key.get());
+                Err(Error::custom(msg, key.span()))
+            };
+            if start > end {
+                return invalid("the start is after the end");
+            }
+            if end > MAX_RANGE_END {
+                return invalid(&format!(
+                    "ranges must end at {MAX_RANGE_END} or less"
+                ));
+            }
+            for (i, index) in (start..=end).enumerate() {
+                check_overlap(&mut defined, index, &key)?;
+                let item = self.instantiate(&item, i, Field::Other)?;
+                let index_key = self.index_key(index, key.span());
+                table.insert_formatted(&index_key, item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the value of `item`, which fills `field`, for the `i`th index
+    /// (0-based) in a range.
+    fn instantiate(
+        &mut self,
+        item: &Item,
+        i: usize,
+        field: Field,
+    ) -> Result<Item, Error> {
+        match item {
+            Item::Table(t) => {
+                if let Some(v) = self.progression(t, t.span(), i, field)? {
+                    return Ok(Item::Value(v));
+                }
+                let mut t = t.clone();
+                for (name, item) in t.iter_mut() {
+                    *item = self.instantiate(item, i, field.child(&name))?;
+                }
+                Ok(Item::Table(t))
+            }
+            Item::Value(v) => {
+                self.instantiate_value(v, i, field).map(Item::Value)
+            }
+            _ => Ok(item.clone()),
+        }
+    }
+
+    fn instantiate_value(
+        &mut self,
+        value: &Value,
+        i: usize,
+        field: Field,
+    ) -> Result<Value, Error> {
+        match value {
+            Value::InlineTable(t) => {
+                let table = t.clone().into_table();
+                if let Some(v) =
+                    self.progression(&table, t.span(), i, field)?
+                {
+                    return Ok(v);
+                }
+                let mut t = t.clone();
+                for (name, v) in t.iter_mut() {
+                    *v = self.instantiate_value(v, i, field.child(&name))?;
+                }
+                Ok(Value::InlineTable(t))
+            }
+            Value::Array(a) if a.is_empty() => {
+                let err: DeserializationError =
+                    de::Error::invalid_length(0, &"at least one value");
+                Err(Error::custom(err, a.span()))
+            }
+            Value::Array(a) => Ok(a.get(i % a.len()).unwrap().clone()),
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// If `table` is a progression, like `{ start = "C3", step = 2 }`, gets
+    /// its `i`th value (0-based). `start` is an integer, or a name of the
+    /// kind `field` accepts (e.g., a note name for `note`), and `step`
+    /// defaults to 1.
+    fn progression(
+        &mut self,
+        table: &Table,
+        span: Option<Range<usize>>,
+        i: usize,
+        field: Field,
+    ) -> Result<Option<Value>, Error> {
+        let Some(start) = table.get("start") else {
+            return Ok(None);
+        };
+        if table.iter().any(|(name, _)| !matches!(name, "start" | "step")) {
+            return Ok(None);
+        }
+        let start = match (start, field) {
+            (Item::Value(Value::Integer(v)), _) => *v.value(),
+            (Item::Value(Value::String(_)), Field::Note) => {
+                let note: MidiNote = deserialize_item(start, &self.aliases)?;
+                note.value().into()
+            }
+            (Item::Value(Value::String(_)), Field::Cc) => {
+                let cc: CcNumber = deserialize_item(start, &self.aliases)?;
+                cc.0.value().into()
+            }
+            (Item::Value(Value::String(_)), Field::Program) => {
+                let prog: ProgramNumber =
+                    deserialize_item(start, &self.aliases)?;
+                prog.0.value().into()
+            }
+            _ => return Err(invalid_type(start, field.expecting())),
+        };
+        let step = match table.get("step") {
+            Some(item) => item
+                .as_integer()
+                .ok_or_else(|| invalid_type(item, "integer"))?,
+            None => 1,
+        };
+        let value = start.saturating_add(step.saturating_mul(i as i64));
+        Ok(Some(self.respan(value.into(), span)))
+    }
+}
+
+/// The field that a value in a range fills, which determines the kind of
+/// name a progression's `start` may be.
+#[derive(Clone, Copy)]
+enum Field {
+    /// A note, like `note` or `note.pitch`.
+    Note,
+    /// A CC number, like `cc` or `cc.number`.
+    Cc,
+    /// A program number, like `prog` or `prog.number`.
+    Program,
+    Other,
+}
+
+impl Field {
+    /// The field filled by the value of `key` in a table that fills `self`.
+    fn child(self, key: &str) -> Self {
+        match (self, key) {
+            (_, "note") => Self::Note,
+            (_, "cc") => Self::Cc,
+            (_, "prog") => Self::Program,
+            (Self::Note, "pitch") => Self::Note,
+            (Self::Cc | Self::Program, "number") => self,
+            _ => Self::Other,
+        }
+    }
+
+    fn expecting(self) -> &'static str {
+        match self {
+            Self::Note => "an integer or note name",
+            Self::Cc => "an integer or controller name",
+            Self::Program => "an integer or program name",
+            Self::Other => "an integer",
+        }
+    }
+}
+
+/// Gets the paths named by the value of `extends`, which is either a single
+/// path or an array of paths.
+fn paths(item: &Item) -> Result<Paths, Error> {
+    let path = |item: &Item, exp| match item.as_str() {
+        Some(s) => Ok((s.to_owned(), item.span())),
+        None => Err(invalid_type(item, exp)),
+    };
+    match item.as_array() {
+        Some(array) => array
+            .iter()
+            .map(|v| path(&Item::Value(v.clone()), "a file path"))
+            .collect(),
+        None => Ok(vec![path(item, "a file path or an array of file paths")?]),
+    }
+}
+
+/// Gets the aliases defined by the value of `aliases`, a table with a table
+/// for each kind of alias.
+fn load_aliases(item: Option<Item>) -> Result<Aliases, Error> {
+    let mut aliases = Aliases::default();
+    let Some(item) = item else {
+        return Ok(aliases);
+    };
+    // Aliases can't refer to each other.
+    let none = Aliases::new();
+    let table = table(&item)
+        .ok_or_else(|| invalid_type(&item, "a table of aliases"))?;
+    for (kind, item) in table.iter() {
+        if !Aliases::KINDS.contains(&kind) {
+            let key = table.key(kind).expect("key should be present");
+            let err: DeserializationError =
+                de::Error::unknown_field(kind, Aliases::KINDS);
+            return Err(Error::custom(err, key.span()));
+        }
+        let entries = self::table(item)
+            .ok_or_else(|| invalid_type(item, "a table of aliases"))?;
+        for (name, item) in entries.iter() {
+            let key = entries.key(name).expect("key should be present");
+            let builtin = match kind {
+                "notes" => {
+                    let note = deserialize_item::<MidiNote>(item, &none)?;
+                    aliases.notes.insert(name.to_owned(), note);
+                    name.parse::<NoteName>().is_ok().then_some("note name")
+                }
+                "cc" => {
+                    let cc = deserialize_item::<CcNumber>(item, &none)?;
+                    aliases.cc.insert(name.to_owned(), cc.0);
+                    gm::controller(name).map(|_| "controller name")
+                }
+                "programs" => {
+                    let prog = deserialize_item::<ProgramNumber>(item, &none)?;
+                    aliases.programs.insert(name.to_owned(), prog.0);
+                    gm::program(name).map(|_| "program name")
+                }
+                _ => unreachable!(),
+            };
+            if let Some(builtin) = builtin {
+                let msg = format!(
+                    "alias `{}` is already a {builtin}",
+                    name.escape_default(),
+                );
+                return Err(Error::custom(msg, key.span()));
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// Deserializes `item`, which must be an integer or a string, using the
+/// names in `aliases`.
+fn deserialize_item<'a, T>(item: &Item, aliases: &Aliases) -> Result<T, Error>
+where
+    T: DeserializeConfig<'a, Aliases>,
+{
+    let result = match item {
+        Item::Value(Value::Integer(v)) => {
+            T::deserialize((*v.value()).into_deserializer(), aliases)
+        }
+        Item::Value(Value::String(v)) => {
+            T::deserialize(v.value().clone().into_deserializer(), aliases)
+        }
+        _ => return Err(invalid_type(item, "an integer or string")),
+    };
+    result.map_err(|e: DeserializationError| Error::custom(e, item.span()))
+}
+
+/// The placeholder for the `i`th generated key or value.
+fn placeholder(i: usize) -> String {
+    format!("{PLACEHOLDER}{i}")
+}
+
+/// Gets the index of the generated key or value that `text` is a
+/// placeholder for. Placeholders are never parsed, so `span` must be
+/// `None`.
+fn placeholder_index(text: &str, span: Option<Range<usize>>) -> Option<usize> {
+    if span.is_some() {
+        return None;
+    }
+    text.strip_prefix(PLACEHOLDER)?.parse().ok()
+}
+
+/// Replaces the placeholders in `table` with the generated keys and values
+/// they stand for, which are taken from `parsed`.
+fn fill_table(table: &mut Table, parsed: &mut Parsed) {
+    if table.iter().any(|(name, _)| name.starts_with(PLACEHOLDER)) {
+        let entries: Vec<_> = table
+            .iter()
+            .map(|(name, item)| {
+                let key = table.key(name).expect("key should be present");
+                (key.clone(), item.clone())
+            })
+            .collect();
+        table.clear();
+        for (key, item) in entries {
+            let key = placeholder_index(key.get(), key.span())
+                .and_then(|i| parsed.keys.remove(&i))
+                .unwrap_or(key);
+            table.insert_formatted(&key, item);
+        }
+    }
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Value(v) => fill_value(v, parsed),
+            Item::Table(t) => fill_table(t, parsed),
+            Item::ArrayOfTables(a) => {
+                a.iter_mut().for_each(|t| fill_table(t, parsed));
+            }
+            Item::None => {}
+        }
+    }
+}
+
+/// Replaces the placeholders in `value`, like [`fill_table`].
+fn fill_value(value: &mut Value, parsed: &mut Parsed) {
+    match value {
+        Value::String(s) => {
+            let index = placeholder_index(s.value(), s.span());
+            if let Some(v) = index.and_then(|i| parsed.values.remove(&i)) {
+                *value = v;
+            }
+        }
+        Value::Array(a) => a.iter_mut().for_each(|v| fill_value(v, parsed)),
+        Value::InlineTable(t) => {
+            t.iter_mut().for_each(|(_, v)| fill_value(v, parsed));
+        }
+        _ => {}
+    }
+}
+
+/// Gets `item` as a table if it's a table with range keys, converting it
+/// from an inline table if needed.
+fn pads_table(item: &mut Item) -> Option<&mut Table> {
+    let has_ranges = table(item)
+        .is_some_and(|t| t.iter().any(|(name, _)| range_key(name).is_some()));
+    if has_ranges {
+        table_mut(item)
+    } else {
+        None
+    }
+}
+
+/// Parses a range key like `1-16` into its start and end, returning `None`
+/// for other keys.
+fn range_key(name: &str) -> Option<(usize, usize)> {
+    let (start, end) = name.split_once('-')?;
+    let is_index =
+        |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_index(start) || !is_index(end) {
+        return None;
+    }
+    Some((start.parse().ok()?, end.parse().ok()?))
+}
+
+/// Gets the span of each character in the raw text of a TOML string,
+/// relative to the start of the text. Escape sequences are one character,
+/// and line-ending backslashes (with the whitespace they remove) are
+/// skipped. Returns `None` if `raw` isn't a string.
+fn char_spans(raw: &str) -> Option<Vec<Range<usize>>> {
+    let delimiter = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|d| raw.starts_with(d) && raw.ends_with(d))?;
+    let literal = delimiter.starts_with('\'');
+    let end = raw.len().checked_sub(delimiter.len())?;
+    let mut i = delimiter.len();
+    if delimiter.len() == 3 {
+        // A newline right after the opening delimiter is trimmed.
+        let rest = raw.get(i..)?;
+        i += ["\r\n", "\n"]
+            .into_iter()
+            .find(|n| rest.starts_with(n))
+            .map_or(0, str::len);
+    }
+    let mut spans = Vec::new();
+    while i < end {
+        let rest = raw.get(i..end)?;
+        let mut chars = rest.chars();
+        let c = chars.next()?;
+        let len = match (literal, c, chars.next()) {
+            (false, '\\', Some('u')) => 6,
+            (false, '\\', Some('U')) => 10,
+            (false, '\\', Some('x')) => 4,
+            (false, '\\', Some(c)) if c.is_whitespace() => {
+                i += 1 + rest[1..].len() - rest[1..].trim_start().len();
+                continue;
+            }
+            (false, '\\', _) => 2,
+            (_, '\r', Some('\n')) => 2,
+            _ => c.len_utf8(),
+        };
+        spans.push(i..i + len);
+        i += len;
+    }
+    Some(spans)
+}
+
+/// Records that `key` defines `index`, returning an error if another key
+/// already did.
+fn check_overlap(
+    defined: &mut HashMap<usize, Key>,
+    index: usize,
+    key: &Key,
+) -> Result<(), Error> {
+    let Some(other) = defined.insert(index, key.clone()) else {
+        return Ok(());
+    };
+    let msg = format!(
+        "keys `{}` and `{}` overlap (both include {index})",
+        other.get().escape_default(),
+        key.get().escape_default(),
+    );
+    Err(Error::custom(msg, key.span()))
+}
+
+fn invalid_type(item: &Item, exp: &str) -> Error {
+    let err: DeserializationError =
+        de::Error::invalid_type(unexpected(item), &exp);
+    Error::custom(err, item.span())
+}
+
+fn unexpected(item: &Item) -> Unexpected<'_> {
+    match item {
+        Item::Value(Value::String(v)) => Unexpected::Str(v.value()),
+        Item::Value(Value::Integer(v)) => Unexpected::Signed(*v.value()),
+        Item::Value(Value::Float(v)) => Unexpected::Float(*v.value()),
+        Item::Value(Value::Boolean(v)) => Unexpected::Bool(*v.value()),
+        Item::Value(Value::Datetime(_)) => Unexpected::Other("datetime"),
+        Item::Value(Value::Array(_)) => Unexpected::Seq,
+        Item::Value(Value::InlineTable(_)) => Unexpected::Map,
+        Item::Table(_) => Unexpected::Map,
+        Item::ArrayOfTables(_) => Unexpected::Seq,
+        Item::None => Unexpected::Unit,
+    }
+}
+
+/// Merges `child` into `base`. Values in `child` replace those in `base`,
+/// except that tables in both are merged recursively.
+fn merge(base: &mut Table, child: &Table) {
+    for (name, item) in child.iter() {
+        let key = child.key(name).expect("key should be present");
+        match (base.get_mut(name).and_then(table_mut), table(item)) {
+            (Some(base), Some(child)) => merge(base, &child),
+            _ => {
+                base.insert_formatted(key, item.clone());
+            }
+        }
+    }
+}
+
+fn table(item: &Item) -> Option<Cow<'_, Table>> {
+    match item {
+        Item::Table(t) => Some(Cow::Borrowed(t)),
+        Item::Value(Value::InlineTable(t)) => {
+            Some(Cow::Owned(t.clone().into_table()))
+        }
+        _ => None,
+    }
+}
+
+/// Like [`Item::as_table_mut`], but converts inline tables to tables first.
+fn table_mut(item: &mut Item) -> Option<&mut Table> {
+    if let Item::Value(Value::InlineTable(t)) = item {
+        *item = Item::Table(core::mem::take(t).into_table());
+    }
+    item.as_table_mut()
+}
+
+/// Moves the keys that must come before the others to the front of `table`
+/// and its subtables: `version`, `device`, and `model`, then `defaults`, then
+/// `legend`. Any of these may have been inherited, so they can't be left in
+/// the order written.
+fn order(table: &mut Table) {
+    const FIRST: [&str; 5] =
+        ["version", "device", "model", "defaults", "legend"];
+    let rank = |k: &Key| {
+        FIRST.iter().position(|&s| s == k.get()).unwrap_or(FIRST.len())
+    };
+    table.sort_values_by(|k1, _, k2, _| rank(k1).cmp(&rank(k2)));
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(table) => order(table),
+            Item::ArrayOfTables(array) => array.iter_mut().for_each(order),
+            _ => {}
+        }
+    }
+}
@@ -103,6 +103,85 @@ where
     OneOf(items)
 }
 
+/// Gets the `candidates` that are close enough to `name` to suggest in its
+/// place, closest first: those a few edits away, then those that contain
+/// `name`.
+pub fn near_matches<'a, C>(name: &str, candidates: C) -> Vec<&'a str>
+where
+    C: IntoIterator<Item = &'a str>,
+{
+    /// The largest number of matches returned.
+    const MAX_MATCHES: usize = 3;
+    let len = name.chars().count();
+    let max_distance = len.max(3) / 3;
+    let mut matches: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|c| match edit_distance(name, c) {
+            d if d <= max_distance => Some((d, c)),
+            _ if len >= 3 && c.contains(name) => Some((max_distance + 1, c)),
+            _ => None,
+        })
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches.into_iter().take(MAX_MATCHES).map(|(_, c)| c).collect()
+}
+
+/// The number of insertions, deletions, substitutions, and transpositions
+/// of adjacent characters needed to turn `a` into `b` (the optimal string
+/// alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().collect();
+    let b: Vec<_> = b.chars().collect();
+    // The distances for the previous two prefixes of `a`.
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<_> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
+
+/// Formats a message suggesting `matches` in place of `name`, a `what` (like
+/// "controller name") that doesn't exist.
+pub fn did_you_mean<'a>(
+    what: &'a str,
+    name: &'a str,
+    matches: &'a [&'a str],
+) -> impl Display + 'a {
+    struct DidYouMean<'a> {
+        what: &'a str,
+        name: &'a str,
+        matches: &'a [&'a str],
+    }
+
+    impl Display for DidYouMean<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "unknown {} `{}`; did you mean {}?",
+                self.what,
+                self.name.escape_default(),
+                one_of(self.matches.iter().copied()),
+            )
+        }
+    }
+
+    DidYouMean {
+        what,
+        name,
+        matches,
+    }
+}
+
 pub fn check_dup<T, E>(v: &Option<T>, name: &'static str) -> Result<(), E>
 where
     E: de::Error,
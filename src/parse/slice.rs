@@ -25,44 +25,70 @@ use serde::de::{self, Deserialize, DeserializeSeed, Deserializer};
 #[derive(Clone, Copy)]
 struct UnknownKey<'a> {
     name: &'a str,
-    max: usize,
+    key: &'a IndexKey,
 }
 
 impl Display for UnknownKey<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "unknown key `{}`; expected an integer between 1 and {}",
-            self.name.escape_default(),
-            self.max,
-        )
+        write!(f, "unknown key `{}`; expected ", self.name.escape_default())?;
+        de::Visitor::expecting(self.key, f)
     }
 }
 
 struct IndexKey {
     max: usize,
+    /// If the items form a grid with this many columns, keys like `r3c5`
+    /// (row 3, column 5) are also accepted.
+    columns: Option<usize>,
+}
+
+impl IndexKey {
+    /// Parses a positive integer without leading zeros.
+    fn number(s: &str) -> Option<usize> {
+        let b = s.as_bytes();
+        (matches!(b, [b'1'..=b'9', ..]) && b.iter().all(u8::is_ascii_digit))
+            .then(|| s.parse().ok())
+            .flatten()
+    }
+
+    /// Parses a key like `r3c5`, returning the 1-based item number.
+    fn position(&self, s: &str) -> Option<usize> {
+        let columns = self.columns?;
+        let (row, col) = s.strip_prefix('r')?.split_once('c')?;
+        let (row, col) = (Self::number(row)?, Self::number(col)?);
+        let rows = self.max / columns;
+        ((1..=rows).contains(&row) && (1..=columns).contains(&col))
+            .then(|| (row - 1) * columns + col)
+    }
 }
 
 impl de::Visitor<'_> for IndexKey {
     type Value = usize;
 
     fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "an integer between 1 and {}", self.max)
+        write!(f, "an integer between 1 and {}", self.max)?;
+        if let Some(columns) = self.columns {
+            write!(
+                f,
+                " or a position from `r1c1` to `r{}c{columns}`",
+                self.max / columns,
+            )?;
+        }
+        Ok(())
     }
 
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        let b = v.as_bytes();
-        (matches!(b, [b'1'..=b'9', ..]) && b.iter().all(u8::is_ascii_digit))
-            .then_some(v)
-            .and_then(|v| v.parse().ok())
-            .and_then(|n| (1..=self.max).contains(&n).then(|| n - 1))
+        Self::number(v)
+            .filter(|n| (1..=self.max).contains(n))
+            .or_else(|| self.position(v))
+            .map(|n| n - 1)
             .ok_or_else(|| {
                 de::Error::custom(UnknownKey {
                     name: v,
-                    max: self.max,
+                    key: &self,
                 })
             })
     }
@@ -97,6 +123,25 @@ impl Display for DuplicateItem {
     }
 }
 
+/// Converts `n`, a 1-based index into `len` items set by a builder, to a
+/// 0-based index, failing if it's out of range. `kind` names the index.
+pub fn builder_index<E: de::Error>(
+    kind: &str,
+    n: usize,
+    len: usize,
+) -> Result<usize, E> {
+    if (1..=len).contains(&n) {
+        Ok(n - 1)
+    } else {
+        Err(E::custom(format_args!("{kind} out of range: {n}")))
+    }
+}
+
+/// Returns the error produced when item `index` (0-based) is missing.
+pub fn missing_item<E: de::Error>(index: usize) -> E {
+    E::custom(MissingItem(index))
+}
+
 pub trait ElementSeed<'a> {
     type Seed: DeserializeSeed<'a>;
     fn get(&self, index: usize) -> Self::Seed;
@@ -131,6 +176,7 @@ where
 pub struct Seed<S> {
     element: S,
     len: usize,
+    columns: Option<usize>,
 }
 
 impl<S> Seed<S> {
@@ -138,13 +184,15 @@ impl<S> Seed<S> {
         Self {
             element,
             len,
+            columns: None,
         }
     }
-}
 
-impl<T> Seed<PhantomData<T>> {
-    pub fn basic(len: usize) -> Self {
-        Self::new(PhantomData, len)
+    /// Treats the items as a grid with `columns` columns, so that they may
+    /// also be keyed by position, like `r3c5` (row 3, column 5).
+    pub fn grid(mut self, columns: usize) -> Self {
+        self.columns = Some(columns);
+        self
     }
 }
 
@@ -186,26 +234,29 @@ where
     where
         A: de::MapAccess<'a>,
     {
-        let mut items = Vec::new();
-        items.resize_with(self.len, || None);
+        let mut items = Inline::new(self.len);
         let key = || IndexKey {
             max: self.len,
+            columns: self.columns,
         };
-        let seed = |i| self.element.get(i);
         while let Some(i) = map.next_key_seed(key())? {
-            if items[i].replace(map.next_value_seed(seed(i))?).is_some() {
-                return Err(de::Error::custom(DuplicateItem(i)));
-            }
-        }
-        for (i, slot) in items.iter_mut().enumerate() {
-            if slot.is_some() {
-            } else if let Some(item) = try_none_seed(seed(i)) {
-                *slot = Some(item);
-            } else {
-                return Err(de::Error::custom(MissingItem(i)));
-            }
+            items.next_value_seed(&mut map, i, self.element.get(i))?;
         }
-        Ok(items.into_iter().map(Option::unwrap).collect())
+        items.finish(self.element)
+    }
+
+    /// Allows a missing sequence if all of its items may be missing, as when
+    /// the sequence is itself an item of another sequence.
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        (0..self.len)
+            .map(|i| {
+                try_none_seed(self.element.get(i))
+                    .ok_or_else(|| E::custom(MissingItem(i)))
+            })
+            .collect()
     }
 }
 
@@ -223,6 +274,70 @@ where
     }
 }
 
+/// Parses `key` as the key of one of `len` items, returning the item's
+/// 0-based index. This is for items keyed directly in a table that has other
+/// keys too; see [`Inline`].
+pub fn item_index<E: de::Error>(key: &str, len: usize) -> Result<usize, E> {
+    de::Visitor::visit_str(
+        IndexKey {
+            max: len,
+            columns: None,
+        },
+        key,
+    )
+}
+
+/// Collects items keyed directly in a table that has other keys too, rather
+/// than in a table of their own.
+pub struct Inline<T> {
+    items: Vec<Option<T>>,
+}
+
+impl<T> Inline<T> {
+    pub fn new(len: usize) -> Self {
+        let mut items = Vec::new();
+        items.resize_with(len, || None);
+        Self {
+            items,
+        }
+    }
+
+    /// Deserializes the next value in `map` as item `index` (0-based).
+    pub fn next_value_seed<'a, A, S>(
+        &mut self,
+        map: &mut A,
+        index: usize,
+        seed: S,
+    ) -> Result<(), A::Error>
+    where
+        A: de::MapAccess<'a>,
+        S: DeserializeSeed<'a, Value = T>,
+    {
+        if self.items[index].replace(map.next_value_seed(seed)?).is_some() {
+            return Err(de::Error::custom(DuplicateItem(index)));
+        }
+        Ok(())
+    }
+
+    /// Returns the items, filling in missing ones as if their values were
+    /// [`None`] and failing if any can't be missing.
+    pub fn finish<'a, S, E>(self, element: S) -> Result<Box<[T]>, E>
+    where
+        S: ElementSeed<'a>,
+        S::Seed: DeserializeSeed<'a, Value = T>,
+        E: de::Error,
+    {
+        self.items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.or_else(|| try_none_seed(element.get(i)))
+                    .ok_or_else(|| E::custom(MissingItem(i)))
+            })
+            .collect()
+    }
+}
+
 pub fn deserialize<'a, T, D>(
     deserializer: D,
     len: usize,
@@ -0,0 +1,125 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for serializing custom modes.
+
+use crate::controls::Optional;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Like [`Serialize`], but with a configuration that affects how the value
+/// is written. This is the counterpart of
+/// [`DeserializeConfig`](crate::parse::config::DeserializeConfig).
+pub trait SerializeConfig<C> {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+        config: &C,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+/// Serializes a value with the given configuration.
+pub struct WithConfig<'a, T, C> {
+    value: &'a T,
+    config: &'a C,
+}
+
+impl<'a, T, C> WithConfig<'a, T, C> {
+    pub fn new(value: &'a T, config: &'a C) -> Self {
+        Self {
+            value,
+            config,
+        }
+    }
+}
+
+impl<T: SerializeConfig<C>, C> Serialize for WithConfig<'_, T, C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer, self.config)
+    }
+}
+
+/// Serializes a slice as a table whose keys are 1-based indices. This is
+/// the counterpart of [`slice::Seed`](crate::parse::slice::Seed).
+pub struct Indexed<'a, T>(pub &'a [T]);
+
+impl<T: Serialize> Serialize for Indexed<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for (i, item) in self.0.iter().enumerate() {
+            map.serialize_entry(&(i + 1).to_string(), item)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes only the present elements of a slice of optional controls,
+/// keyed by their 1-based indices.
+pub struct IndexedOptional<'a, T>(pub &'a [Optional<T>]);
+
+impl<'a, T> IndexedOptional<'a, T> {
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|item| item.as_ref().is_none())
+    }
+
+    /// Serializes each element with the configuration returned by `config`
+    /// for that element's (0-based) index.
+    pub fn serialize_with<S, C, F>(
+        &self,
+        serializer: S,
+        config: F,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: SerializeConfig<C>,
+        F: Fn(usize) -> C,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for (i, item) in self.0.iter().enumerate() {
+            if let Some(item) = item.as_ref() {
+                let config = config(i);
+                let value = WithConfig::new(item, &config);
+                map.serialize_entry(&(i + 1).to_string(), &value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl<T: Serialize> Serialize for IndexedOptional<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for (i, item) in self.0.iter().enumerate() {
+            if let Some(item) = item.as_ref() {
+                map.serialize_entry(&(i + 1).to_string(), item)?;
+            }
+        }
+        map.end()
+    }
+}
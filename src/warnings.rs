@@ -0,0 +1,47 @@
+/*
+ * Copyright (C) 2024 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Warnings found while deserializing a custom mode.
+//!
+//! Deserialization can't return warnings alongside its result, so types that
+//! find a problem worth mentioning record it in the [`Warnings`] passed to
+//! them in their deserialization config.
+
+use super::error::Warning;
+use std::cell::RefCell;
+
+/// The warnings recorded while deserializing a custom mode.
+#[derive(Debug, Default)]
+pub struct Warnings(RefCell<Vec<Warning>>);
+
+impl Warnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `warning`.
+    pub fn warn(&self, warning: Warning) {
+        self.0.borrow_mut().push(warning);
+    }
+
+    /// Returns the recorded warnings, in the order they were recorded.
+    pub fn into_vec(self) -> Vec<Warning> {
+        self.0.into_inner()
+    }
+}
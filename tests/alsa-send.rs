@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Tests for `ncc-alsa-send`. A binary can't be used as a library, so its
+//! modules are included here directly.
+
+#[allow(dead_code)]
+#[path = "../src/bin/ncc-alsa-send/family.rs"]
+mod family;
+#[cfg(target_os = "linux")]
+#[allow(dead_code)]
+#[path = "../src/bin/ncc-alsa-send/rawmidi.rs"]
+mod rawmidi;
+#[allow(dead_code)]
+#[path = "../src/bin/ncc-alsa-send/reply.rs"]
+mod reply;
+
+use family::Family;
+use ncc::launchkey::{flkey, flkey_mini, launchkey_mini_mk3};
+use ncc::launchpad::launchpad_x;
+use reply::{Expected, Firmware, Identity, Outcome};
+
+#[cfg(target_os = "linux")]
+#[test]
+fn port_names() {
+    let parse =
+        |s: &str| rawmidi::PortName::parse(s.as_ref()).map(|p| p.to_string());
+    assert_eq!(parse("hw:3,0,0").as_deref(), Some("hw:3,0,0"));
+    assert_eq!(parse("hw:3,1").as_deref(), Some("hw:3,1"));
+    assert_eq!(parse("hw:3").as_deref(), Some("hw:3,0"));
+    assert_eq!(parse("hw:MiniMK3").as_deref(), Some("hw:MiniMK3,0"));
+    let path = "/dev/snd/midiC3D0";
+    assert_eq!(parse(path).as_deref(), Some(path));
+    for port in ["hw:", "hw:,0", "hw:3,x", "hw:3,0,", "hw:3,0,0,0", "virtual"]
+    {
+        assert!(parse(port).is_none(), "{port}");
+    }
+}
+
+#[test]
+fn sysex_messages() {
+    let data = b"\xf8\xf0\x01\xf7\xfe\xf0\x02\x03\xf7\xf0\x04";
+    let messages: Vec<_> = reply::sysex_messages(data).collect();
+    assert_eq!(messages, [&b"\xf0\x01\xf7"[..], b"\xf0\x02\x03\xf7"]);
+}
+
+#[test]
+fn amidi_dump() {
+    let output = b"F0 7E 00 06 02 00 20 29\n03 01 00 00 00 02 00 01 F7\n";
+    assert_eq!(
+        reply::parse_hex(output),
+        b"\xf0\x7e\x00\x06\x02\x00\x20\x29\
+        \x03\x01\x00\x00\x00\x02\x00\x01\xf7",
+    );
+    assert_eq!(reply::parse_hex(b"F0 xyz 1 F7 F7F7\n"), b"\xf0\xf7");
+}
+
+#[test]
+fn check_reply() {
+    let syx = include_bytes!("syx-data/launchpad-x/blank.syx");
+    let expected = Expected::from_syx(syx).unwrap();
+    assert!(Expected::from_syx(b"\xf0\x7e\x7f\x06\x01\xf7").is_none());
+
+    let check = |received: &[u8]| expected.check(Some(received));
+    assert_eq!(expected.check(None), Outcome::Timeout);
+    // Timing clock and active sensing.
+    assert_eq!(check(b"\xf8\xfe"), Outcome::Timeout);
+
+    // A SysEx message from a Launchpad X, after a late reply to a device
+    // inquiry.
+    let inquiry = b"\xf0\x7e\x00\x06\x02\x00\x20\x29\x03\x01\x00\x00\
+        \x00\x02\x00\x01\xf7";
+    let own = b"\xf0\x00\x20\x29\x02\x0c\x00\xf7";
+    assert_eq!(check(own), Outcome::Responded);
+    assert_eq!(check(&[&inquiry[..], own].concat()), Outcome::Responded);
+    assert_eq!(check(inquiry), Outcome::Unrecognized);
+    // A SysEx message from a Launchkey Mini MK3.
+    assert_eq!(
+        check(b"\xf0\x00\x20\x29\x02\x0b\x00\xf7"),
+        Outcome::Unrecognized
+    );
+    // An incomplete message.
+    assert_eq!(check(b"\xf0\x00\x20\x29\x02\x0c"), Outcome::Unrecognized);
+}
+
+#[test]
+fn family_ports() {
+    let family = |id| Family::from_device_id(id).unwrap();
+    let mini = family(launchkey_mini_mk3::DEVICE_ID);
+    assert!(mini.matches("Launchkey Mini MK3"));
+    assert!(mini.matches("Launchkey Mini MK3 MIDI 1"));
+    assert!(!mini.matches("Launchpad X"));
+    assert!(family(launchpad_x::DEVICE_ID).matches("Launchpad X LPX MIDI"));
+
+    let flkey = family(flkey::DEVICE_ID);
+    let flkey_mini = family(flkey_mini::DEVICE_ID);
+    assert!(flkey.matches("FLkey 37 MIDI 1"));
+    assert!(!flkey.matches("FLkey Mini MIDI 1"));
+    assert!(flkey_mini.matches("FLkey Mini MIDI 1"));
+    assert!(!flkey_mini.matches("FLkey 37 MIDI 1"));
+    assert!(Family::from_device_id(0x7f).is_none());
+}
+
+#[test]
+fn identity() {
+    let reply = b"\xf0\x7e\x00\x06\x02\x00\x20\x29\x35\x01\x00\x00\
+        \x00\x01\x01\x01\xf7";
+    let identity = Identity::parse(reply).unwrap();
+    assert_eq!(identity.family, 0x0135);
+    assert_eq!(identity.model, 0);
+    assert_eq!(identity.firmware, Firmware([0, 1, 1, 1]));
+    assert_eq!(identity.firmware.to_string(), "1.1.1");
+    let family = Family::from_inquiry_code(identity.family).unwrap();
+    assert_eq!(family.device_id, launchkey_mini_mk3::DEVICE_ID);
+
+    // Preceded by active sensing and another SysEx message.
+    let other = b"\xfe\xf0\x00\x20\x29\x02\x0c\x00\xf7";
+    let identity = Identity::parse(&[&other[..], reply].concat()).unwrap();
+    assert_eq!(identity.family, 0x0135);
+
+    // Not Novation.
+    let mut wrong = reply.to_vec();
+    wrong[7] = 0x2a;
+    assert!(Identity::parse(&wrong).is_none());
+    assert!(Identity::parse(&reply[..reply.len() - 2]).is_none());
+    assert!(Identity::parse(b"\xf0\x7e\x7f\x06\x01\xf7").is_none());
+
+    assert_eq!(Firmware([0, 2, 0, 1]).to_string(), "2.0.1");
+    assert_eq!(Firmware([1, 0, 2, 3]).to_string(), "10.2.3");
+    assert!(Firmware([0, 2, 0, 0]) < Firmware([0, 2, 0, 1]));
+    assert!(Firmware([0, 9, 9, 9]) < Firmware([1, 0, 0, 0]));
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (C) 2024-2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![deny(unsafe_code)]
+
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+macro_rules! define_tests {
+    (
+        syx = $syx_prefix:literal
+        $(, $func:ident($path:literal $(,)?))* $(,)?
+    ) => {
+        $(#[test]
+        fn $func() {
+            TestCase {
+                name: $path,
+                syx: include_bytes!(concat!($syx_prefix, $path, ".syx")),
+            }
+            .assert();
+        })*
+    };
+}
+
+define_tests! {
+    syx = "syx-data/",
+    flkey_mini_colors_1("flkey-mini/colors/1"),
+    flkey_mini_colors_2("flkey-mini/colors/2"),
+    flkey_mini_colors_3("flkey-mini/colors/3"),
+    flkey_mini_colors_4("flkey-mini/colors/4"),
+    flkey_mini_colors_5("flkey-mini/colors/5"),
+    flkey_mini_colors_6("flkey-mini/colors/6"),
+    flkey_mini_colors_7("flkey-mini/colors/7"),
+    flkey_mini_colors_8("flkey-mini/colors/8"),
+    flkey_mini_default_pads("flkey-mini/default-pads"),
+    flkey_mini_default_pedal("flkey-mini/default-pedal"),
+    flkey_mini_default_pots("flkey-mini/default-pots"),
+    flkey_mini_example_pads("flkey-mini/example-pads"),
+    flkey_mini_example_pedal("flkey-mini/example-pedal"),
+    flkey_mini_example_pots("flkey-mini/example-pots"),
+    flkey_colors_1("flkey/colors/1"),
+    flkey_colors_2("flkey/colors/2"),
+    flkey_colors_3("flkey/colors/3"),
+    flkey_colors_4("flkey/colors/4"),
+    flkey_colors_5("flkey/colors/5"),
+    flkey_colors_6("flkey/colors/6"),
+    flkey_colors_7("flkey/colors/7"),
+    flkey_colors_8("flkey/colors/8"),
+    flkey_default_faders("flkey/default-faders"),
+    flkey_default_pads("flkey/default-pads"),
+    flkey_default_pedal("flkey/default-pedal"),
+    flkey_default_pots("flkey/default-pots"),
+    flkey_example_faders("flkey/example-faders"),
+    flkey_example_pads("flkey/example-pads"),
+    flkey_example_pedal("flkey/example-pedal"),
+    flkey_example_pots("flkey/example-pots"),
+    launchkey_mini_mk3_colors_1("launchkey-mini-mk3/colors/1"),
+    launchkey_mini_mk3_colors_2("launchkey-mini-mk3/colors/2"),
+    launchkey_mini_mk3_colors_3("launchkey-mini-mk3/colors/3"),
+    launchkey_mini_mk3_colors_4("launchkey-mini-mk3/colors/4"),
+    launchkey_mini_mk3_colors_5("launchkey-mini-mk3/colors/5"),
+    launchkey_mini_mk3_colors_6("launchkey-mini-mk3/colors/6"),
+    launchkey_mini_mk3_colors_7("launchkey-mini-mk3/colors/7"),
+    launchkey_mini_mk3_colors_8("launchkey-mini-mk3/colors/8"),
+    launchkey_mini_mk3_default_pads("launchkey-mini-mk3/default-pads"),
+    launchkey_mini_mk3_default_pedal("launchkey-mini-mk3/default-pedal"),
+    launchkey_mini_mk3_default_pots("launchkey-mini-mk3/default-pots"),
+    launchkey_mini_mk3_example_pads("launchkey-mini-mk3/example-pads"),
+    launchkey_mini_mk3_example_pedal("launchkey-mini-mk3/example-pedal"),
+    launchkey_mini_mk3_example_pots("launchkey-mini-mk3/example-pots"),
+    launchkey_mk3_colors_1("launchkey-mk3/colors/1"),
+    launchkey_mk3_colors_2("launchkey-mk3/colors/2"),
+    launchkey_mk3_colors_3("launchkey-mk3/colors/3"),
+    launchkey_mk3_colors_4("launchkey-mk3/colors/4"),
+    launchkey_mk3_colors_5("launchkey-mk3/colors/5"),
+    launchkey_mk3_colors_6("launchkey-mk3/colors/6"),
+    launchkey_mk3_colors_7("launchkey-mk3/colors/7"),
+    launchkey_mk3_colors_8("launchkey-mk3/colors/8"),
+    launchkey_mk3_default_faders("launchkey-mk3/default-faders"),
+    launchkey_mk3_default_pads("launchkey-mk3/default-pads"),
+    launchkey_mk3_default_pedal("launchkey-mk3/default-pedal"),
+    launchkey_mk3_default_pots("launchkey-mk3/default-pots"),
+    launchkey_mk3_example_faders("launchkey-mk3/example-faders"),
+    launchkey_mk3_example_pads("launchkey-mk3/example-pads"),
+    launchkey_mk3_example_pedal("launchkey-mk3/example-pedal"),
+    launchkey_mk3_example_pots("launchkey-mk3/example-pots"),
+    launchpad_mini_mk3_blank("launchpad-mini-mk3/blank"),
+    launchpad_mini_mk3_colors_1("launchpad-mini-mk3/colors/1"),
+    launchpad_mini_mk3_colors_2("launchpad-mini-mk3/colors/2"),
+    launchpad_mini_mk3_example("launchpad-mini-mk3/example"),
+    launchpad_x_blank("launchpad-x/blank"),
+    launchpad_x_colors_1("launchpad-x/colors/1"),
+    launchpad_x_colors_2("launchpad-x/colors/2"),
+    launchpad_x_example("launchpad-x/example"),
+}
+
+#[test]
+fn decompile_define() {
+    let syx = include_bytes!("syx-data/launchpad-x/blank.syx");
+    let result = run_ncc(&["decompile", "-D", "x=1", "-", "-o-"], syx);
+    assert!(matches!(result, Err(Fail::ChildStatus)));
+    let result = run_ncc(&["-D", "x=1", "decompile", "-", "-o-"], syx);
+    assert!(matches!(result, Err(Fail::ChildStatus)));
+}
+
+struct TestCase {
+    name: &'static str,
+    syx: &'static [u8],
+}
+
+const BIN: &str = if cfg!(debug_assertions) {
+    "target/debug/ncc"
+} else {
+    "target/release/ncc"
+};
+
+/// Runs ncc with `args`, passing `input` to stdin and returning stdout.
+fn run_ncc(args: &[&str], input: &[u8]) -> Result<Vec<u8>, Fail> {
+    let mut child = Command::new(BIN)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(Fail::Spawn)?;
+    let mut stdin = child.stdin.take().unwrap();
+    let output = (|| {
+        stdin.write_all(input)?;
+        stdin.flush()?;
+        drop(stdin);
+        child.wait_with_output()
+    })()
+    .map_err(Fail::ChildIo)?;
+    if !output.status.success() {
+        return Err(Fail::ChildStatus);
+    }
+    Ok(output.stdout)
+}
+
+impl TestCase {
+    pub fn run(&self) -> Result<(), Fail> {
+        let toml = run_ncc(&["decompile", "-", "-o-"], self.syx)?;
+        let syx = run_ncc(&["-", "-o-"], &toml)?;
+        if syx != self.syx {
+            return Err(Fail::BadSyx);
+        }
+        Ok(())
+    }
+
+    pub fn assert(&self) {
+        if let Err(e) = self.run() {
+            panic!("error running test \"{}\": {e}", self.name);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Fail {
+    Spawn(io::Error),
+    ChildIo(io::Error),
+    ChildStatus,
+    BadSyx,
+}
+
+impl Display for Fail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn(e) => write!(f, "could not create child process: {e}"),
+            Self::ChildIo(e) => {
+                write!(f, "could not communicate with child process: {e}")
+            }
+            Self::ChildStatus => {
+                write!(f, "child process exited unsuccessfully")
+            }
+            Self::BadSyx => {
+                write!(f, "recompiled sysex differs from original")
+            }
+        }
+    }
+}
@@ -0,0 +1,752 @@
+/*
+ * Copyright (C) 2024-2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of ncc.
+ *
+ * ncc is free software: you can redistribute it and/or modify it under
+ * the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * ncc is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+ * or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General
+ * Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public
+ * License along with ncc. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![deny(unsafe_code)]
+
+use ncc::ansi::{self, AnsiWriter};
+use ncc::launchkey::Model;
+use ncc::{Cc, CustomMode, Device, Fader, MidiNote, MidiValue, Note};
+use ncc::{Input, LaunchpadX, Orientation, Pad, Pickup, Pot, Velocity};
+use ncc::{LaunchkeyMiniMk3Pots, LaunchkeyMk3Faders, LaunchkeyMk3Pots};
+use ncc::{LaunchkeyMk3, LaunchkeyMk3Pads};
+use std::fs;
+use std::path::Path;
+
+/// The usual `active-color` line for modes that need one.
+const ACTIVE: &str = "active-color = 1\n";
+
+/// Prefixes `body` with the `version` and `device` keys.
+fn toml(device: &str, body: &str) -> String {
+    format!("version = 2\ndevice = \"{device}\"\n{body}")
+}
+
+/// Parses and compiles a custom mode for `device` with the keys in `body`.
+fn compile(device: &str, body: &str) -> Result<Vec<u8>, String> {
+    let mode = ncc::parse(&toml(device, body)).map_err(|e| e.to_string())?;
+    let mut syx = Vec::new();
+    mode.compile(&mut syx).map_err(|e| e.to_string())?;
+    Ok(syx)
+}
+
+#[test]
+fn compile_example() {
+    let toml = include_str!("../examples/launchpad-x/example.toml");
+    let syx = include_bytes!("syx-data/launchpad-x/example.syx");
+    let mode = ncc::parse(toml).unwrap();
+    assert_eq!(mode.device(), Device::LaunchpadX);
+    assert!(matches!(mode, CustomMode::LaunchpadX(_)));
+    let mut compiled = Vec::new();
+    mode.compile(&mut compiled).unwrap();
+    assert_eq!(compiled, syx);
+}
+
+#[test]
+fn error_span() {
+    let toml = "version = 2\ndevice = \"launchkey-mk3-pedal\"\ncc = 200\n";
+    let err = ncc::parse(toml).unwrap_err();
+    let span = err.span().unwrap();
+    assert_eq!(&toml[span], "200");
+}
+
+#[test]
+fn serialize_round_trip() {
+    let toml = include_str!("../examples/launchkey-mk3/example-pads.toml");
+    let mode = ncc::parse(toml).unwrap();
+    let serialized = mode.to_toml().unwrap();
+    assert!(serialized.starts_with("version = 2\n"));
+    let reparsed = ncc::parse(&serialized).unwrap();
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    mode.compile(&mut a).unwrap();
+    reparsed.compile(&mut b).unwrap();
+    assert_eq!(a, b);
+}
+
+fn value(v: u8) -> MidiValue {
+    MidiValue::new(v).unwrap()
+}
+
+#[test]
+fn builder_matches_toml() {
+    let toml = "\
+        version = 2\n\
+        device = \"launchpad-x\"\n\
+        name = \"Built\"\n\
+        active-color = 5\n\
+        [pads.10]\n\
+        color = 3\n\
+        note = 60\n\
+        [faders.8]\n\
+        orientation = \"vertical\"\n\
+        color = 4\n\
+        cc = 20\n\
+    ";
+    let map = LaunchpadX::builder()
+        .name("Built".parse().unwrap())
+        .active_color(value(5))
+        .pad(2, 2, Pad::note(value(3), Note::new(MidiNote(value(60)))))
+        .and_then(|b| {
+            b.fader(8, Fader::new(Orientation::Vertical, value(4), value(20)))
+        })
+        .and_then(|b| b.build())
+        .unwrap();
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    CustomMode::from(map).compile(&mut a).unwrap();
+    ncc::parse(toml).unwrap().compile(&mut b).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn builder_conflict() {
+    let toml = "\
+        version = 2\n\
+        device = \"launchpad-x\"\n\
+        active-color = 5\n\
+        [pads.3]\n\
+        color = 3\n\
+        cc = 1\n\
+        [faders.1]\n\
+        orientation = \"horizontal\"\n\
+        color = 4\n\
+        cc = 20\n\
+    ";
+    let msg = "cannot define both pad r1c3 and fader 1 (horizontal, r1), \
+        which includes pad r1c3";
+    let err = LaunchpadX::builder()
+        .active_color(value(5))
+        .pad(1, 3, Pad::cc(value(3), Cc::new(value(1))))
+        .and_then(|b| {
+            b.fader(
+                1,
+                Fader::new(Orientation::Horizontal, value(4), value(20)),
+            )
+        })
+        .and_then(|b| b.build())
+        .unwrap_err();
+    assert_eq!(err.to_string(), msg);
+    assert!(ncc::parse(toml).unwrap_err().to_string().contains(msg));
+}
+
+#[test]
+fn builder_fixed_velocity() {
+    let mut note = Note::new(MidiNote(value(60)));
+    note.velocity = Velocity::Fixed(value(100));
+    let err = LaunchpadX::builder()
+        .active_color(value(5))
+        .pad(1, 1, Pad::note(value(3), note))
+        .and_then(|b| b.build())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid type: integer `100`; expected \"variable\"",
+    );
+}
+
+#[test]
+fn builder_last_button_color() {
+    let button = |color| Pad::cc(value(color), Cc::new(value(1)));
+    let builder = LaunchkeyMk3Faders::builder()
+        .active_color(value(5))
+        .button(1, button(7))
+        .and_then(|b| b.button(9, button(7)))
+        .unwrap();
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the color of this button cannot be customized",
+    );
+    builder.button(9, button(2)).and_then(|b| b.build()).unwrap();
+}
+
+#[test]
+fn builder_missing_pot() {
+    let err = LaunchkeyMk3Pots::builder()
+        .pot(1, Pot::new(value(21)))
+        .and_then(|b| b.build())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "missing item 2 in sequence");
+}
+
+#[test]
+fn builder_out_of_range() {
+    let pad = Pad::note(value(3), Note::new(MidiNote(value(60))));
+    let err = LaunchpadX::builder().pad(9, 1, pad).unwrap_err();
+    assert_eq!(err.to_string(), "pad row out of range: 9");
+    let err =
+        LaunchkeyMk3Pots::builder().pot(0, Pot::new(value(21))).unwrap_err();
+    assert_eq!(err.to_string(), "pot number out of range: 0");
+}
+
+#[test]
+fn builder_pickup() {
+    let toml = "\
+        version = 2\n\
+        device = \"launchkey-mini-mk3-pots\"\n\
+        [pots.1]\n\
+        cc = 21\n\
+        pickup = \"global\"\n\
+    ";
+    let msg = "string \"global\"; expected `true` or `false`";
+    let mut pot = Pot::new(value(21));
+    let builder = (1..=8)
+        .try_fold(LaunchkeyMiniMk3Pots::builder(), |b, n| b.pot(n, pot))
+        .unwrap();
+    assert!(builder.build().unwrap_err().to_string().contains(msg));
+    assert!(ncc::parse(toml).unwrap_err().to_string().contains(msg));
+    pot.pickup = Pickup::No;
+    let builder = (1..=8).try_fold(builder, |b, n| b.pot(n, pot)).unwrap();
+    builder.build().unwrap();
+}
+
+#[test]
+fn model_without_faders() {
+    let toml = "\
+        version = 2\n\
+        device = \"launchkey-mk3-faders\"\n\
+        model = \"37\"\n\
+        active-color = 5\n\
+    ";
+    let err = ncc::parse(toml).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "\"37\"");
+    assert!(err.to_string().contains("the 37-key model has no faders"));
+    let toml = toml.replace("\"37\"", "\"61\"");
+    ncc::parse(&toml).unwrap();
+    let toml = toml.replace("launchkey-mk3", "flkey").replace("61", "88");
+    assert!(ncc::parse(&toml).is_err());
+}
+
+#[test]
+fn model_unsupported() {
+    let toml = "\
+        version = 2\n\
+        device = \"launchkey-mini-mk3-pads\"\n\
+        model = \"25\"\n\
+        active-color = 5\n\
+    ";
+    let err = ncc::parse(toml).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "model");
+}
+
+#[test]
+fn builder_model() {
+    let err = LaunchkeyMk3Faders::builder()
+        .model(Model::Keys25)
+        .active_color(value(5))
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "the 25-key model has no faders");
+}
+
+#[test]
+fn combined_map() {
+    let pads = "active-color = 5\n[pads.1]\ncolor = 3\nnote = 60\n";
+    let pedal = "cc = 64\n";
+    let toml =
+        toml("launchkey-mk3", &format!("[pads]\n{pads}[pedal]\n{pedal}"));
+    let mode = ncc::parse(&toml).unwrap();
+    assert_eq!(mode.device(), Device::LaunchkeyMk3);
+    let mut combined = Vec::new();
+    mode.compile(&mut combined).unwrap();
+
+    let mut separate = Vec::new();
+    for (device, map) in [("pads", pads), ("pedal", pedal)] {
+        let device = format!("launchkey-mk3-{device}");
+        separate.extend(compile(&device, map).unwrap());
+    }
+    assert_eq!(combined, separate);
+
+    let decompiled = ncc::decompile::decompile(&combined).unwrap();
+    assert!(decompiled.contains("device = \"launchkey-mk3\"\n"));
+    assert!(decompiled.contains("[pads.1]\n"), "{decompiled}");
+    let reparsed = ncc::parse(&decompiled).unwrap();
+    let mut recompiled = Vec::new();
+    reparsed.compile(&mut recompiled).unwrap();
+    assert_eq!(recompiled, combined);
+
+    // Range keys work in the pads section too.
+    assert_eq!(
+        compile(
+            "launchkey-mk3",
+            "[pads]\nactive-color = 5\n[pads.\"1-2\"]\ncolor = 3\n\
+            note = { start = 60 }\n",
+        ),
+        compile(
+            "launchkey-mk3",
+            "[pads]\nactive-color = 5\n1 = { color = 3, note = 60 }\n\
+            2 = { color = 3, note = 61 }\n",
+        ),
+    );
+}
+
+#[test]
+fn combined_map_model() {
+    let faders = "[faders]\nactive-color = 5\n[faders.1]\ncc = 20\n";
+    let mode = ncc::parse(&toml(
+        "launchkey-mk3",
+        &format!("model = \"49\"\n{faders}"),
+    ))
+    .unwrap();
+    let serialized = mode.to_toml().unwrap();
+    assert!(serialized.contains("\nmodel = \"49\"\n"), "{serialized}");
+    assert!(serialized.contains("[faders.1]\n"), "{serialized}");
+    ncc::parse(&serialized).unwrap();
+
+    let err = compile("launchkey-mk3", &format!("model = \"25\"\n{faders}"))
+        .unwrap_err();
+    assert_eq!(err, "the 25-key model has no faders");
+
+    // The model is given once, for the whole custom mode.
+    let toml = toml(
+        "launchkey-mk3",
+        &faders.replace("[faders.1]", "model = \"49\"\n[faders.1]"),
+    );
+    let err = ncc::parse(&toml).unwrap_err();
+    assert!(err.to_string().starts_with("unknown key `model`"), "{err}");
+    assert_eq!(&toml[err.span().unwrap()], "model");
+
+    let err = LaunchkeyMk3::builder()
+        .pads(
+            LaunchkeyMk3Pads::builder()
+                .model(Model::Keys49)
+                .active_color(value(5))
+                .build()
+                .unwrap(),
+        )
+        .faders(
+            LaunchkeyMk3Faders::builder()
+                .model(Model::Keys61)
+                .active_color(value(5))
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "the sections are for different models");
+}
+
+#[test]
+fn combined_map_empty() {
+    let toml = "version = 2\ndevice = \"launchkey-mini-mk3\"\n";
+    let err = ncc::parse(toml).unwrap_err();
+    assert!(err.to_string().contains("no maps defined"));
+}
+
+#[test]
+fn extends() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("extends");
+    fs::create_dir_all(&dir).unwrap();
+    let base = "version = 2\ndevice = \"launchpad-x\"\nname = \"Base\"\n\
+        active-color = 1\n\
+        [pads.1]\ncolor = 5\nnote = 60\n[pads.2]\ncolor = 300\nnote = 61\n";
+    fs::write(dir.join("base.toml"), base).unwrap();
+
+    let child = "extends = \"base.toml\"\n[pads.2]\ncolor = 6\n";
+    let input = Input {
+        path: &dir.join("child.toml"),
+        text: child,
+    };
+    let mut extended = Vec::new();
+    ncc::parse_input(input, &[]).unwrap().compile(&mut extended).unwrap();
+    let merged = "name = \"Base\"\nactive-color = 1\n\
+        [pads.1]\ncolor = 5\nnote = 60\n[pads.2]\ncolor = 6\nnote = 61\n";
+    assert_eq!(Ok(extended), compile("launchpad-x", merged));
+
+    // The error comes from the base file, so its span refers to that file.
+    let child = "extends = \"base.toml\"\nname = \"Child\"\n";
+    let input = Input {
+        path: &dir.join("child.toml"),
+        text: child,
+    };
+    let err = ncc::parse_input(input, &[]).unwrap_err();
+    assert_eq!(&base[err.span().unwrap()], "300");
+}
+
+#[test]
+fn extends_missing() {
+    let toml = "extends = [\"missing.toml\"]\n";
+    let input = Input {
+        path: Path::new("nonexistent/child.toml"),
+        text: toml,
+    };
+    let err = ncc::parse_input(input, &[]).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "\"missing.toml\"");
+}
+
+#[test]
+fn vars() {
+    let toml = "version = 2\ndevice = \"launchkey-mini-mk3-pads\"\n\
+        active-color = 1\n[vars]\nchannel = 16\nroot = 60\n\
+        [pads.1]\ncolor = 5\n\
+        note = { pitch = \"${root}\", channel = \"${channel}\" }\n";
+    let input = Input {
+        path: Path::new("vars.toml"),
+        text: toml,
+    };
+    let mut defined = Vec::new();
+    ncc::parse_input(input, &[("channel", "3"), ("root", "C4")])
+        .unwrap()
+        .compile(&mut defined)
+        .unwrap();
+    let plain = "active-color = 1\n[pads.1]\ncolor = 5\n\
+        note = { pitch = \"C4\", channel = 3 }\n";
+    assert_eq!(Ok(defined), compile("launchkey-mini-mk3-pads", plain));
+
+    // Errors in substituted values point at the reference.
+    let err = ncc::parse_input(input, &[("channel", "17")]).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "\"${channel}\"");
+    let err = ncc::parse(&toml.replace("${root}", "${missing}")).unwrap_err();
+    assert_eq!(err.to_string(), "undefined variable `missing`");
+
+    // `$${` is a literal `${`.
+    let toml =
+        toml.replace("[vars]", "name = \"$${root} is ${root}\"\n[vars]");
+    let mode = ncc::parse(&toml).unwrap().to_toml().unwrap();
+    assert!(mode.contains("name = \"${root} is 60\""), "{mode}");
+}
+
+#[test]
+fn defaults() {
+    let defaulted = "active-color = 1\n\
+        [pads.1]\nnote = 60\n[pads.2]\ncolor = 9\ncc = 3\n\
+        [defaults.pad]\ncolor = 5\nchannel = 3\nbehavior = \"toggle\"\n";
+    let plain = "active-color = 1\n\
+        [pads.1]\ncolor = 5\n\
+        note = { pitch = 60, channel = 3, behavior = \"toggle\" }\n\
+        [pads.2]\ncolor = 9\n\
+        cc = { number = 3, channel = 3, behavior = \"toggle\" }\n";
+    assert_eq!(
+        compile("launchkey-mini-mk3-pads", defaulted),
+        compile("launchkey-mini-mk3-pads", plain),
+    );
+
+    // Launchpad pads must have variable velocity.
+    let toml = "version = 2\ndevice = \"launchpad-x\"\nactive-color = 1\n\
+        [defaults.pad]\nvelocity = 5\n";
+    let err = ncc::parse(toml).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "5");
+}
+
+#[test]
+fn ranges() {
+    let ranged = "active-color = 1\n\
+        [pads.\"1-3\"]\ncolor = [5, 9]\nnote = { start = \"C3\", step = 2 }\n\
+        [pads.\"4-5\".cc]\nnumber = { start = 20 }\n\
+        [pads.\"4-5\"]\ncolor = 3\n";
+    let plain = "active-color = 1\n\
+        pads.1 = { color = 5, note = \"C3\" }\n\
+        pads.2 = { color = 9, note = \"D3\" }\n\
+        pads.3 = { color = 5, note = \"E3\" }\n\
+        pads.4 = { color = 3, cc = 20 }\n\
+        pads.5 = { color = 3, cc = 21 }\n";
+    assert_eq!(
+        compile("launchkey-mini-mk3-pads", ranged),
+        compile("launchkey-mini-mk3-pads", plain),
+    );
+
+    let toml = "version = 2\ndevice = \"launchkey-mini-mk3-pads\"\n\
+        active-color = 1\n\
+        [pads.\"1-8\"]\ncolor = 5\nnote = 60\n[pads.3]\ncolor = 1\nnote = 3\n";
+    let err = ncc::parse(toml).unwrap_err();
+    assert_eq!(err.to_string(), "keys `1-8` and `3` overlap (both include 3)");
+    assert_eq!(&toml[err.span().unwrap()], "3");
+
+    // Only pad tables have range keys.
+    let toml = "version = 2\ndevice = \"launchkey-mini-mk3-pots\"\n\
+        [pots.\"1-8\"]\ncc = 3\n";
+    let err = ncc::parse(toml).unwrap_err();
+    assert!(err.to_string().starts_with("unknown key `1-8`"));
+    assert_eq!(&toml[err.span().unwrap()], "\"1-8\"");
+}
+
+#[test]
+fn ranges_from_vars() {
+    // The progression comes from a variable, so the error in pad 9 (note
+    // 128) is traced through both substitutions.
+    let toml = "version = 2\ndevice = \"launchpad-x\"\nactive-color = 1\n\
+        [vars]\nn = { start = 120 }\n\
+        [pads.\"1-16\"]\ncolor = 5\nnote = \"${n}\"\n";
+    let err = ncc::parse(toml).unwrap_err();
+    assert_eq!(&toml[err.span().unwrap()], "\"${n}\"");
+    let input = Input {
+        path: Path::new("vars.toml"),
+        text: toml,
+    };
+    let mut writer = AnsiWriter::new(Vec::new(), ansi::Mode::Plain);
+    err.show(&mut writer, input).unwrap();
+    let shown = String::from_utf8(writer.into_inner().0).unwrap();
+    assert!(shown.starts_with("error in vars.toml, line 8, column 8:"));
+}
+
+#[test]
+fn grid_positions() {
+    let (a, b) = ("{ color = 3, note = 60 }", "{ color = 4, note = 61 }");
+    let forms = [
+        format!("[pads]\n21 = {a}\n22 = {b}\n"),
+        format!("[pads]\nr3c5 = {a}\nr3c6 = {b}\n"),
+        format!("[grid.3]\n5 = {a}\n6 = {b}\n"),
+        format!(
+            "[rows]\n3 = [{{}}, {{}}, {{}}, {{}}, {a}, {b}, {{}}, {{}}]\n"
+        ),
+    ];
+    let compiled: Vec<_> = forms
+        .iter()
+        .map(|form| compile("launchpad-x", &format!("{ACTIVE}{form}")))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(compiled.iter().all(|syx| *syx == compiled[0]));
+
+    let body = format!("{ACTIVE}[pads]\nr2c1 = {a}\n[grid.2]\n1 = {b}\n");
+    let err = compile("launchpad-x", &body).unwrap_err();
+    assert_eq!(err, "pad r2c1 is defined in both `pads` and `grid`");
+}
+
+#[test]
+fn layout() {
+    let legend = "[legend]\nK = { color = 3, note = 60 }\n";
+    let rows = ["........"; 6].join("\n");
+    let body = format!(
+        "{ACTIVE}layout = \"\"\"\n..K.....\n  ....K...  \n{rows}\n\"\"\"\n\
+        {legend}",
+    );
+    let pads = "[pads]\nr1c3 = { color = 3, note = 60 }\n\
+        r2c5 = { color = 3, note = 60 }\n";
+    assert_eq!(
+        compile("launchpad-x", &body),
+        compile("launchpad-x", &format!("{ACTIVE}{pads}")),
+    );
+
+    let toml = toml("launchpad-x", &body.replacen("....K...", "....X...", 1));
+    let err = ncc::parse(&toml).unwrap_err();
+    assert!(err.to_string().contains("no legend entry for `X`"));
+    assert_eq!(&toml[err.span().unwrap()], "X");
+
+    // A layout from a variable is reported at the reference.
+    let toml = self::toml(
+        "launchpad-x",
+        &format!(
+            "{ACTIVE}layout = \"${{L}}\"\n\
+            [vars]\nL = \"\"\"\n..K.....\n....X...\n{rows}\n\"\"\"\n{legend}",
+        ),
+    );
+    let err = ncc::parse(&toml).unwrap_err();
+    assert!(err.to_string().contains("no legend entry for `X`"));
+    assert_eq!(&toml[err.span().unwrap()], "\"${L}\"");
+    let input = Input {
+        path: Path::new("layout.toml"),
+        text: &toml,
+    };
+    let mut writer = AnsiWriter::new(Vec::new(), ansi::Mode::Plain);
+    err.show(&mut writer, input).unwrap();
+    let shown = String::from_utf8(writer.into_inner().0).unwrap();
+    assert!(shown.starts_with("error in layout.toml, line 4, column 10:"));
+
+    // Only Launchpad modes have a layout.
+    let err = compile(
+        "launchkey-mini-mk3-pads",
+        &format!("{ACTIVE}layout = \"..K.....\"\n"),
+    )
+    .unwrap_err();
+    assert!(err.starts_with("unknown key `layout`"));
+}
+
+#[test]
+fn generate_scale() {
+    // In A minor pentatonic with rows a scale degree apart, pads 57 and 49
+    // (the first column of the bottom two rows) play A and C, and pad 58
+    // replaces its generated note.
+    let generated = compile(
+        "launchpad-x",
+        &format!(
+            "{ACTIVE}[generate.scale]\nroot = \"A2\"\n\
+            scale = \"minor-pentatonic\"\nin-key-only = true\n\
+            row-offset = 1\nroot-color = 5\nscale-color = 9\n\
+            [pads.58]\nnote = 1\ncolor = 7\n",
+        ),
+    )
+    .unwrap();
+    let decompiled = ncc::decompile::decompile(&generated).unwrap();
+    for expected in [
+        "[pads.57]\ncolor = 5\nnote = 45\n",
+        "[pads.49]\ncolor = 9\nnote = 48\n",
+        "[pads.58]\ncolor = 7\nnote = 1\n",
+    ] {
+        assert!(decompiled.contains(expected), "{decompiled}");
+    }
+
+    let body = format!("{ACTIVE}[generate.scale]\nscale = \"minor\"\n");
+    let err = compile("launchpad-x", &body).unwrap_err();
+    assert_eq!(err, "missing key `root`");
+}
+
+#[test]
+fn note_aliases() {
+    let aliases = "[aliases.notes]\nmy-kick = \"gm:bass-drum-1\"\n";
+    assert_eq!(
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}{aliases}[pads.1]\nnote = \"my-kick\"\ncolor = 5\n\
+                [pads.2]\nnote = \"gm:acoustic-snare\"\ncolor = 5\n",
+            ),
+        ),
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}[pads.1]\nnote = 36\ncolor = 5\n\
+                [pads.2]\nnote = 38\ncolor = 5\n",
+            ),
+        ),
+    );
+
+    let toml = toml(
+        "launchpad-x",
+        &format!("{ACTIVE}{aliases}[pads.1]\nnote = \"my-kik\"\ncolor = 5\n"),
+    );
+    let err = ncc::parse(&toml).unwrap_err();
+    assert!(err.to_string().starts_with("invalid value: string \"my-kik\""));
+    assert_eq!(&toml[err.span().unwrap()], "\"my-kik\"");
+}
+
+#[test]
+fn named_values() {
+    assert_eq!(
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}[aliases.cc]\ncutoff = 74\n\
+                [pads.1]\ncc = \"expression\"\ncolor = 5\n\
+                [pads.2]\ncc = {{ number = \"cutoff\" }}\ncolor = 5\n\
+                [pads.3]\nprog = \"electric-piano-1\"\ncolor = 5\n",
+            ),
+        ),
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}[pads.1]\ncc = 11\ncolor = 5\n\
+                [pads.2]\ncc = 74\ncolor = 5\n\
+                [pads.3]\nprog = 4\ncolor = 5\n",
+            ),
+        ),
+    );
+
+    let toml = toml(
+        "launchpad-x",
+        &format!("{ACTIVE}[pads.1]\ncc = \"modulaton\"\ncolor = 5\n"),
+    );
+    let err = ncc::parse(&toml).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown controller name `modulaton`; did you mean `modulation`?",
+    );
+    assert_eq!(&toml[err.span().unwrap()], "\"modulaton\"");
+
+    // Names also start progressions in ranges.
+    assert_eq!(
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}[pads.\"1-2\"]\ncolor = 5\n\
+                cc = {{ number = {{ start = \"modulation\" }} }}\n\
+                [pads.\"3-4\"]\ncolor = 5\n\
+                prog = {{ start = \"electric-piano-1\" }}\n",
+            ),
+        ),
+        compile(
+            "launchpad-x",
+            &format!(
+                "{ACTIVE}[pads.1]\ncc = 1\ncolor = 5\n\
+                [pads.2]\ncc = 2\ncolor = 5\n\
+                [pads.3]\nprog = 4\ncolor = 5\n\
+                [pads.4]\nprog = 5\ncolor = 5\n",
+            ),
+        ),
+    );
+    let body = format!(
+        "{ACTIVE}[pads.\"1-2\"]\nnote = 60\ncolor = {{ start = \"red\" }}\n",
+    );
+    let err = compile("launchpad-x", &body).unwrap_err();
+    assert_eq!(err, "invalid type: string \"red\"; expected an integer");
+}
+
+#[test]
+fn keypress_names() {
+    assert_eq!(
+        compile(
+            "flkey-pads",
+            "active-color = 3\n\
+            [pads.1]\ncolor = 1\nkeypress = \"ctrl+alt+Delete\"\n\
+            [pads.2]\ncolor = 1\nkeypress = \"space\"\n",
+        ),
+        compile(
+            "flkey-pads",
+            "active-color = 3\n[pads.1]\ncolor = 1\n\
+            keypress = { keycode = 0x4c, ctrl = true, alt = true }\n\
+            [pads.2]\ncolor = 1\nkeypress = 0x2c\n",
+        ),
+    );
+
+    let toml = toml(
+        "flkey-pads",
+        "active-color = 3\n[pads.1]\ncolor = 1\nkeypress = \"Entr\"\n",
+    );
+    let err = ncc::parse(&toml).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown key name `Entr`; did you mean `Enter`?",
+    );
+    assert_eq!(&toml[err.span().unwrap()], "\"Entr\"");
+}
+
+#[test]
+fn color_names() {
+    assert_eq!(
+        compile(
+            "launchpad-x",
+            "active-color = \"amber\"\n\
+            [pads.1]\nnote = 60\ncolor = \"red\"\n\
+            [pads.2]\nnote = 61\ncolor = \"#ff8800\"\n",
+        ),
+        compile(
+            "launchpad-x",
+            "active-color = 96\n\
+            [pads.1]\nnote = 60\ncolor = 5\n\
+            [pads.2]\nnote = 61\ncolor = 84\n",
+        ),
+    );
+
+    let toml = toml(
+        "launchpad-x",
+        &format!("{ACTIVE}[pads.1]\nnote = 60\ncolor = \"#800080\"\n"),
+    );
+    let input = ncc::Input {
+        path: "".as_ref(),
+        text: &toml,
+    };
+    let (_, warnings) = ncc::parse_input_with_warnings(input, &[]).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].to_string(),
+        "no color in the palette is close to `#800080`; using the closest, \
+        51 (`#7661b3`)",
+    );
+    assert_eq!(&toml[warnings[0].span().unwrap()], "\"#800080\"");
+
+    let body = format!("{ACTIVE}[pads.1]\nnote = 60\ncolor = \"rde\"\n");
+    let err = compile("launchpad-x", &body).unwrap_err();
+    assert_eq!(err, "unknown color `rde`; did you mean `red`?");
+}